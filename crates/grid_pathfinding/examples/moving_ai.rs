@@ -0,0 +1,115 @@
+//! Solves every scenario of a Moving AI `.scen` file with each algorithm and compares the cost of
+//! the paths with the published optimal lengths:
+//!
+//! ```text
+//! cargo run --release -p grid_pathfinding --example moving_ai -- arena.map arena.map.scen
+//! ```
+//!
+//! DFS, IDDFS and IDA* can take exponentially long on open maps, so every search gives up after
+//! `MAX_EXPANSIONS` node expansions, or the number given after the scenario file.
+
+use std::env;
+use std::process;
+use std::time::Duration;
+
+use grid_pathfinding::{
+    load_moving_ai_map, load_moving_ai_scenarios, run_scenarios, Algorithm, HierarchicalMap,
+    PathError, PathOptions, PathfindingAlgorithm, CLUSTER_SIZE,
+};
+
+const MAX_EXPANSIONS: usize = 1_000_000;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (map_path, scenario_path, max_expansions) = match args.as_slice() {
+        [map_path, scenario_path] => (map_path, scenario_path, Some(MAX_EXPANSIONS)),
+        [map_path, scenario_path, max_expansions] if max_expansions.parse::<usize>().is_ok() => {
+            (map_path, scenario_path, max_expansions.parse().ok())
+        }
+        _ => {
+            println!("Usage: moving_ai <file.map> <file.map.scen> [max expansions]");
+            process::exit(2);
+        }
+    };
+    let path_options = PathOptions { max_expansions };
+    let grid = load_moving_ai_map(map_path).unwrap_or_else(|error| {
+        println!("Could not load {map_path}: {error}");
+        process::exit(1);
+    });
+    let scenarios = load_moving_ai_scenarios(scenario_path).unwrap_or_else(|error| {
+        println!("Could not load {scenario_path}: {error}");
+        process::exit(1);
+    });
+    if let Some(scenario) = scenarios
+        .iter()
+        .find(|scenario| scenario.width != grid.width || scenario.height != grid.height)
+    {
+        println!(
+            "Warning: {scenario_path} was made for a {}x{} map, but {map_path} is {}x{}.",
+            scenario.width, scenario.height, grid.width, grid.height
+        );
+    }
+    println!(
+        "{} scenarios on a {}x{} map.",
+        scenarios.len(),
+        grid.width,
+        grid.height
+    );
+    println!(
+        "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8} {:>16} {:>12}",
+        "Algorithm",
+        "Solved",
+        "Optimal",
+        "Costlier",
+        "Cheaper",
+        "Gave Up",
+        "Nodes Expanded",
+        "Time (ms)"
+    );
+    // HPA* reuses one abstract graph for all scenarios, like it would in a game.
+    let hierarchical_map = HierarchicalMap::new(&grid, CLUSTER_SIZE);
+    for pathfinding_algorithm in PathfindingAlgorithm::ALL {
+        let algorithm: &dyn Algorithm =
+            if pathfinding_algorithm == PathfindingAlgorithm::HierarchicalAStar {
+                &hierarchical_map
+            } else {
+                &pathfinding_algorithm
+            };
+        let results = run_scenarios(&grid, &scenarios, algorithm, &path_options);
+        let solved = results
+            .iter()
+            .filter(|result| result.cost().is_some())
+            .count();
+        let optimal = results.iter().filter(|result| result.is_optimal()).count();
+        let cheaper = results
+            .iter()
+            .filter(|result| {
+                result
+                    .cost()
+                    .zip(result.scenario.optimal_cost())
+                    .is_some_and(|(cost, optimal_cost)| cost < optimal_cost)
+            })
+            .count();
+        let gave_up = results
+            .iter()
+            .filter(|result| matches!(result.result, Err(PathError::ExpansionLimitReached { .. })))
+            .count();
+        let nodes_expanded: usize = results
+            .iter()
+            .filter_map(|result| result.result.as_ref().ok())
+            .map(|result| result.nodes_expanded)
+            .sum();
+        let elapsed: Duration = results.iter().map(|result| result.elapsed).sum();
+        println!(
+            "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8} {:>16} {:>12.1}",
+            pathfinding_algorithm.to_string(),
+            solved,
+            optimal,
+            solved - optimal - cheaper,
+            cheaper,
+            gave_up,
+            nodes_expanded,
+            elapsed.as_secs_f64() * 1000.0
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use super::{Grid, PathError, Position, Search};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathfindingAlgorithm {
+    AStar,
+    BidirectionalAStar,
+    BFS,
+    BidirectionalBFS,
+    Dijkstra,
+    JumpPointSearch,
+    ThetaStar,
+    LazyThetaStar,
+    DStarLite,
+    HierarchicalAStar,
+    FlowField,
+    WeightedAStar,
+    ARAStar,
+    GreedyBestFirst,
+    DFS,
+    IDDFS,
+    IDAStar,
+}
+
+impl PathfindingAlgorithm {
+    pub const ALL: [PathfindingAlgorithm; 17] = [
+        PathfindingAlgorithm::AStar,
+        PathfindingAlgorithm::BidirectionalAStar,
+        PathfindingAlgorithm::BFS,
+        PathfindingAlgorithm::BidirectionalBFS,
+        PathfindingAlgorithm::Dijkstra,
+        PathfindingAlgorithm::JumpPointSearch,
+        PathfindingAlgorithm::ThetaStar,
+        PathfindingAlgorithm::LazyThetaStar,
+        PathfindingAlgorithm::DStarLite,
+        PathfindingAlgorithm::HierarchicalAStar,
+        PathfindingAlgorithm::FlowField,
+        PathfindingAlgorithm::WeightedAStar,
+        PathfindingAlgorithm::ARAStar,
+        PathfindingAlgorithm::GreedyBestFirst,
+        PathfindingAlgorithm::DFS,
+        PathfindingAlgorithm::IDDFS,
+        PathfindingAlgorithm::IDAStar,
+    ];
+}
+
+impl fmt::Display for PathfindingAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PathfindingAlgorithm::AStar => "AStar",
+            PathfindingAlgorithm::BidirectionalAStar => "Bidirectional AStar",
+            PathfindingAlgorithm::BFS => "BFS",
+            PathfindingAlgorithm::BidirectionalBFS => "Bidirectional BFS",
+            PathfindingAlgorithm::Dijkstra => "Dijkstra",
+            PathfindingAlgorithm::JumpPointSearch => "Jump Point Search",
+            PathfindingAlgorithm::ThetaStar => "Theta*",
+            PathfindingAlgorithm::LazyThetaStar => "Lazy Theta*",
+            PathfindingAlgorithm::DStarLite => "D* Lite",
+            PathfindingAlgorithm::HierarchicalAStar => "HPA*",
+            PathfindingAlgorithm::FlowField => "Flow Field",
+            PathfindingAlgorithm::WeightedAStar => "Weighted AStar",
+            PathfindingAlgorithm::ARAStar => "ARA*",
+            PathfindingAlgorithm::GreedyBestFirst => "Greedy Best-First",
+            PathfindingAlgorithm::DFS => "DFS",
+            PathfindingAlgorithm::IDDFS => "IDDFS",
+            PathfindingAlgorithm::IDAStar => "IDA*",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Anything that can start a search between two positions on a grid.
+pub trait Algorithm {
+    /// Creates a search that can then be stepped or run to completion.
+    ///
+    /// # Errors
+    /// Returns an error if the algorithm can't search this grid, e.g. Jump Point Search or
+    /// Theta* on a grid with non-uniform costs.
+    fn start_search(
+        &self,
+        grid: &Grid,
+        start: Position,
+        goal: Position,
+    ) -> Result<Box<dyn Search>, PathError>;
+}
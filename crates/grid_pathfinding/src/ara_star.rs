@@ -0,0 +1,248 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::{whole_cost, Grid, Heuristic, Position, Search, SearchStatus};
+
+/// How much epsilon drops after every path ARA* finds.
+pub const ARA_STAR_EPSILON_STEP: f64 = 0.5;
+
+/// ARA* (Anytime Repairing A*) runs weighted A* with a large epsilon to find a first path quickly,
+/// then lowers epsilon and reuses its search to improve the path, until epsilon reaches 1 and the
+/// path is the cheapest one. Every path it publishes comes with a bound on how far from the
+/// cheapest path it may be, so it can be stopped whenever the path is good enough.
+#[derive(Debug)]
+pub struct AraStar {
+    goal: Position,
+    // The heuristic and the cost of the cheapest open tile it is scaled by.
+    heuristic: (Heuristic, i32),
+    epsilon: f64,
+    g_costs: HashMap<Position, i32>,
+    came_from: HashMap<Position, Position>,
+    // The heap may hold stale entries, an entry only counts while its node is open and its f cost
+    // is still up to date.
+    queue: BinaryHeap<Reverse<(i32, i32, Position)>>,
+    open: HashSet<Position>,
+    closed: HashSet<Position>,
+    // Nodes that got cheaper after they were expanded in this iteration, they are expanded again
+    // in the next one.
+    inconsistent: HashSet<Position>,
+    current: Option<Position>,
+    status: SearchStatus,
+    path: Vec<Position>,
+    path_cost: Option<i32>,
+    bound: Option<f64>,
+    nodes_expanded: usize,
+}
+
+impl AraStar {
+    #[must_use]
+    pub fn new(start: Position, goal: Position, heuristic: (Heuristic, i32), weight: f64) -> Self {
+        let mut ara_star = AraStar {
+            goal,
+            heuristic,
+            epsilon: weight.max(1.0),
+            g_costs: HashMap::new(),
+            came_from: HashMap::new(),
+            queue: BinaryHeap::new(),
+            open: HashSet::new(),
+            closed: HashSet::new(),
+            inconsistent: HashSet::new(),
+            current: None,
+            status: SearchStatus::Searching,
+            path: Vec::new(),
+            path_cost: None,
+            bound: None,
+            nodes_expanded: 0,
+        };
+        ara_star.g_costs.insert(start, 0);
+        ara_star.push(start);
+        ara_star
+    }
+
+    /// The epsilon of the current iteration.
+    #[must_use]
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    fn heuristic(&self, position: Position) -> i32 {
+        let (heuristic, min_cost) = self.heuristic;
+        heuristic.estimate(position, self.goal, min_cost)
+    }
+
+    fn f_cost(&self, position: Position) -> i32 {
+        let h_cost = f64::from(self.heuristic(position)) * self.epsilon;
+        self.g_costs[&position] + whole_cost(h_cost)
+    }
+
+    fn push(&mut self, position: Position) {
+        self.open.insert(position);
+        let entry = (self.f_cost(position), self.heuristic(position), position);
+        self.queue.push(Reverse(entry));
+    }
+
+    // The lowest f cost left in the open set, dropping stale entries on the way.
+    fn peek(&mut self) -> Option<i32> {
+        while let Some(Reverse((f_cost, _, position))) = self.queue.peek().copied() {
+            if self.open.contains(&position) && f_cost == self.f_cost(position) {
+                return Some(f_cost);
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    fn pop(&mut self) -> Option<Position> {
+        self.peek()?;
+        let Reverse((_, _, position)) = self.queue.pop()?;
+        self.open.remove(&position);
+        Some(position)
+    }
+
+    // Publishes the path of the iteration that just ended, then starts the next one with a lower
+    // epsilon, or finishes once the path is known to be the cheapest one.
+    fn finish_iteration(&mut self, grid: &Grid) -> SearchStatus {
+        self.current = None;
+        if !self.g_costs.contains_key(&self.goal) {
+            self.status = SearchStatus::NotFound;
+            return self.status;
+        }
+        // Nodes on the path may have become cheaper since the goal was reached through them, so
+        // the path can cost less than the g cost of the goal.
+        self.path = self.reconstruct_path();
+        let goal_cost = grid.path_cost(&self.path);
+        self.path_cost = Some(goal_cost);
+
+        // Every cheaper path has to pass through a node that is open or inconsistent, so the
+        // cheapest of those without the weight is a lower bound on the cheapest path.
+        let lower_bound = self
+            .open
+            .iter()
+            .chain(&self.inconsistent)
+            .map(|position| self.g_costs[position] + self.heuristic(*position))
+            .min();
+        // The lower bound can drop between iterations, while the bound of the last path still
+        // holds for this one, which costs no more.
+        let bound = match lower_bound {
+            Some(lower_bound) if lower_bound < goal_cost => self
+                .epsilon
+                .min(f64::from(goal_cost) / f64::from(lower_bound.max(1)))
+                .min(self.bound.unwrap_or(f64::INFINITY)),
+            _ => 1.0,
+        };
+        self.bound = Some(bound);
+        if bound <= 1.0 {
+            self.status = SearchStatus::Found;
+            return self.status;
+        }
+
+        self.epsilon = (self.epsilon - ARA_STAR_EPSILON_STEP).max(1.0);
+        let inconsistent: Vec<Position> = self.inconsistent.drain().collect();
+        self.open.extend(inconsistent);
+        self.closed.clear();
+        // The f costs of every open node changed with epsilon.
+        self.queue.clear();
+        let open: Vec<Position> = self.open.iter().copied().collect();
+        for position in open {
+            self.push(position);
+        }
+        self.status
+    }
+
+    fn reconstruct_path(&self) -> Vec<Position> {
+        let mut path = vec![self.goal];
+        let mut position = self.goal;
+        while let Some(previous) = self.came_from.get(&position) {
+            path.push(*previous);
+            position = *previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl Search for AraStar {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        // An iteration ends once no open node could lead to a path cheaper than the one to the
+        // goal at the current epsilon.
+        let goal_cost = self.g_costs.get(&self.goal).copied();
+        let min_f_cost = self.peek();
+        let iteration_done = match (goal_cost, min_f_cost) {
+            (_, None) => true,
+            (Some(goal_cost), Some(min_f_cost)) => goal_cost <= min_f_cost,
+            (None, Some(_)) => false,
+        };
+        if iteration_done {
+            return self.finish_iteration(grid);
+        }
+        let Some(position) = self.pop() else {
+            return self.status;
+        };
+        self.current = Some(position);
+        self.closed.insert(position);
+        self.nodes_expanded += 1;
+
+        let g_cost = self.g_costs[&position];
+        for successor in grid.get_successors(&position) {
+            let new_g_cost = g_cost + successor.cost;
+            if self
+                .g_costs
+                .get(&successor.position)
+                .is_some_and(|&old_g_cost| old_g_cost <= new_g_cost)
+            {
+                continue;
+            }
+            self.g_costs.insert(successor.position, new_g_cost);
+            self.came_from.insert(successor.position, position);
+            if self.closed.contains(&successor.position) {
+                self.inconsistent.insert(successor.position);
+            } else {
+                self.push(successor.position);
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        self.open.iter().copied().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.closed.iter().copied().collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.path.clone()
+    }
+
+    fn cost(&self) -> Option<i32> {
+        self.path_cost
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.g_costs.get(&position).copied()
+    }
+
+    fn suboptimality_bound(&self) -> Option<f64> {
+        self.bound.filter(|bound| *bound > 1.0)
+    }
+}
+
+// References
+// 1. ARA*: Anytime A* with Provable Bounds on Sub-Optimality, Likhachev, Gordon and Thrun,
+// NIPS 2003
@@ -0,0 +1,325 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::{Grid, Position, Search, SearchStatus, STRAIGHT_STEP_COST};
+
+const INFINITY: i32 = i32::MAX / 4;
+
+type Key = (i32, i32);
+
+/// D* Lite searches backwards from the goal and keeps its search state around, so after a few
+/// tiles change or the start moves, only the nodes whose cost to the goal changed are expanded
+/// again.
+#[derive(Debug)]
+pub struct DStarLite {
+    start: Position,
+    last_start: Position,
+    goal: Position,
+    key_modifier: i32,
+    // D* Lite plans on its own copy of the grid, which `repair` and `step` bring up to date.
+    grid: Grid,
+    g_costs: HashMap<Position, i32>,
+    rhs_costs: HashMap<Position, i32>,
+    open: HashMap<Position, Key>,
+    queue: BinaryHeap<Reverse<(Key, Position)>>,
+    current: Option<Position>,
+    status: SearchStatus,
+    nodes_expanded: usize,
+}
+
+impl DStarLite {
+    #[must_use]
+    pub fn new(grid: &Grid, start: Position, goal: Position) -> Self {
+        let mut d_star_lite = DStarLite {
+            start,
+            last_start: start,
+            goal,
+            key_modifier: 0,
+            grid: grid.clone(),
+            g_costs: HashMap::new(),
+            rhs_costs: HashMap::new(),
+            open: HashMap::new(),
+            queue: BinaryHeap::new(),
+            current: None,
+            status: SearchStatus::Searching,
+            nodes_expanded: 0,
+        };
+        d_star_lite.rhs_costs.insert(goal, 0);
+        let key = d_star_lite.calculate_key(goal);
+        d_star_lite.insert(goal, key);
+        d_star_lite
+    }
+
+    /// Brings the search up to date with the grid, start and goal, and returns how many nodes
+    /// had to be expanded to repair the path, or `None` if nothing changed.
+    pub fn repair(&mut self, grid: &Grid, start: Position, goal: Position) -> Option<usize> {
+        // The search tree is rooted at the goal, so a new goal means starting over.
+        if goal != self.goal {
+            *self = DStarLite::new(grid, start, goal);
+            self.run(grid);
+            return Some(self.nodes_expanded);
+        }
+        let moved = start != self.start;
+        if moved {
+            self.key_modifier += self.heuristic(self.last_start, start);
+            self.last_start = start;
+            self.start = start;
+        }
+        if !self.update_grid(grid) && !moved {
+            return None;
+        }
+        let nodes_expanded_before = self.nodes_expanded;
+        self.status = SearchStatus::Searching;
+        self.run(grid);
+        Some(self.nodes_expanded - nodes_expanded_before)
+    }
+
+    // Takes in the tiles that changed since the grid was copied, so that the next expansions
+    // repair the costs they affect, and returns whether any did. A grid of another size or
+    // diagonal policy starts the search over.
+    fn update_grid(&mut self, grid: &Grid) -> bool {
+        if grid.width != self.grid.width
+            || grid.height != self.grid.height
+            || grid.diagonal_policy != self.grid.diagonal_policy
+        {
+            *self = DStarLite::new(grid, self.start, self.goal);
+            return true;
+        }
+        let changed_tiles = grid.changed_tiles(&self.grid);
+        if changed_tiles.is_empty() {
+            return false;
+        }
+        self.grid = grid.clone();
+        for tile in changed_tiles {
+            self.update_vertex(tile);
+            for predecessor in self.predecessors(tile) {
+                self.update_vertex(predecessor);
+            }
+        }
+        true
+    }
+
+    fn g(&self, position: Position) -> i32 {
+        self.g_costs.get(&position).copied().unwrap_or(INFINITY)
+    }
+
+    fn rhs(&self, position: Position) -> i32 {
+        self.rhs_costs.get(&position).copied().unwrap_or(INFINITY)
+    }
+
+    // Manhattan distance overestimates once diagonals are allowed, so use Chebyshev distance.
+    fn heuristic(&self, from: Position, to: Position) -> i32 {
+        let dx = (to.0 - from.0).abs();
+        let dy = (to.1 - from.1).abs();
+        let steps = if self.grid.allows_diagonals() {
+            dx.max(dy)
+        } else {
+            dx + dy
+        };
+        steps * STRAIGHT_STEP_COST
+    }
+
+    fn calculate_key(&self, position: Position) -> Key {
+        let cost = self.g(position).min(self.rhs(position));
+        (
+            cost + self.heuristic(self.start, position) + self.key_modifier,
+            cost,
+        )
+    }
+
+    // The tiles that can move into `position`. Blocked tiles are included, `get_successors`
+    // never checks the tile it starts from, so the start may be standing on one.
+    fn predecessors(&self, position: Position) -> Vec<Position> {
+        let mut predecessors = Vec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let is_diagonal = dx != 0 && dy != 0;
+                if (dx == 0 && dy == 0) || (is_diagonal && !self.grid.allows_diagonals()) {
+                    continue;
+                }
+                let (x, y) = (position.0 + dx, position.1 + dy);
+                if self.grid.in_bounds(x, y) {
+                    predecessors.push(Position(x, y));
+                }
+            }
+        }
+        predecessors
+    }
+
+    fn insert(&mut self, position: Position, key: Key) {
+        self.open.insert(position, key);
+        self.queue.push(Reverse((key, position)));
+    }
+
+    // Nodes are removed from `open` straight away, but stay in the queue until they reach the top.
+    fn top(&mut self) -> Option<(Key, Position)> {
+        while let Some(Reverse((key, position))) = self.queue.peek().copied() {
+            if self.open.get(&position) == Some(&key) {
+                return Some((key, position));
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    fn update_vertex(&mut self, position: Position) {
+        if position != self.goal {
+            let rhs = self
+                .grid
+                .get_successors(&position)
+                .iter()
+                .map(|successor| (successor.cost + self.g(successor.position)).min(INFINITY))
+                .min()
+                .unwrap_or(INFINITY);
+            self.rhs_costs.insert(position, rhs);
+        }
+        self.open.remove(&position);
+        if self.g(position) != self.rhs(position) {
+            let key = self.calculate_key(position);
+            self.insert(position, key);
+        }
+    }
+}
+
+impl Search for DStarLite {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        let start_key = self.calculate_key(self.start);
+        let top = self.top();
+        let start_consistent = self.g(self.start) == self.rhs(self.start);
+        let Some((old_key, position)) =
+            top.filter(|(key, _)| *key < start_key || !start_consistent)
+        else {
+            // The grid may have been edited between steps, which has to be repaired before the
+            // search can end. Comparing it is too slow to do after every expansion.
+            if self.update_grid(grid) {
+                return self.status;
+            }
+            self.current = None;
+            self.status = if self.g(self.start) < INFINITY {
+                SearchStatus::Found
+            } else {
+                SearchStatus::NotFound
+            };
+            return self.status;
+        };
+        self.queue.pop();
+        self.open.remove(&position);
+        self.current = Some(position);
+
+        let new_key = self.calculate_key(position);
+        if old_key < new_key {
+            self.insert(position, new_key);
+        } else if self.g(position) > self.rhs(position) {
+            self.g_costs.insert(position, self.rhs(position));
+            self.nodes_expanded += 1;
+            for predecessor in self.predecessors(position) {
+                self.update_vertex(predecessor);
+            }
+        } else {
+            self.g_costs.insert(position, INFINITY);
+            self.nodes_expanded += 1;
+            self.update_vertex(position);
+            for predecessor in self.predecessors(position) {
+                self.update_vertex(predecessor);
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        self.open.keys().copied().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.g_costs
+            .iter()
+            .filter(|(_, g_cost)| **g_cost < INFINITY)
+            .map(|(position, _)| *position)
+            .collect()
+    }
+
+    // Follows the cheapest successor from the start, the search stores costs to the goal.
+    fn path(&self) -> Vec<Position> {
+        if self.status != SearchStatus::Found {
+            return Vec::new();
+        }
+        let mut path = vec![self.start];
+        let mut position = self.start;
+        while position != self.goal && path.len() <= self.grid.blocked.len() {
+            let next = self
+                .grid
+                .get_successors(&position)
+                .iter()
+                .min_by_key(|successor| successor.cost + self.g(successor.position))
+                .map(|successor| successor.position);
+            let Some(next) = next else {
+                return Vec::new();
+            };
+            path.push(next);
+            position = next;
+        }
+        path
+    }
+
+    fn cost(&self) -> Option<i32> {
+        if self.status == SearchStatus::Found {
+            Some(self.g(self.start))
+        } else {
+            None
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.g_costs
+            .get(&position)
+            .copied()
+            .filter(|g_cost| *g_cost < INFINITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiagonalPolicy;
+
+    #[test]
+    fn steps_repair_grid_edits_made_while_searching() {
+        let mut grid = Grid::new(5, 5, DiagonalPolicy::Never);
+        let (start, goal) = (Position(0, 2), Position(4, 2));
+        let mut d_star_lite = DStarLite::new(&grid, start, goal);
+        for _ in 0..3 {
+            assert_eq!(d_star_lite.step(&grid), SearchStatus::Searching);
+        }
+        let index = grid.xy_idx(2, 2);
+        grid.blocked[index] = true;
+        let mut steps = 0;
+        while d_star_lite.step(&grid) == SearchStatus::Searching {
+            steps += 1;
+            assert!(steps < 1000, "the search did not finish");
+        }
+        assert_eq!(d_star_lite.status, SearchStatus::Found);
+        let path = d_star_lite.path();
+        assert!(!path.contains(&Position(2, 2)));
+        assert_eq!(d_star_lite.cost(), Some(grid.path_cost(&path)));
+        assert_eq!(d_star_lite.cost(), Some(6 * STRAIGHT_STEP_COST));
+    }
+}
+
+// References
+// 1. D* Lite, Sven Koenig and Maxim Likhachev
+// http://idm-lab.org/bib/abstracts/papers/aaai02b.pdf
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Grid, Heuristic, Position, Search, SearchStatus, Successor};
+
+// DFS goes as deep as it can and never returns to a tile it reached before. IDDFS runs it again
+// and again with a growing limit on the number of steps, IDA* with a growing limit on g + h.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Limit {
+    None,
+    Steps,
+    Cost,
+}
+
+// A node on the current branch and the successors of it that are still left to try.
+#[derive(Debug)]
+struct Frame {
+    position: Position,
+    g_cost: i32,
+    successors: Vec<Successor>,
+    next: usize,
+}
+
+/// Depth-first search, iterative deepening DFS and IDA*. They only keep the current branch on a
+/// stack instead of a frontier, and follow every branch as deep as they may before backtracking,
+/// which is why the paths of DFS wander all over the map.
+#[derive(Debug)]
+pub struct DepthFirstSearch {
+    start: Position,
+    goal: Position,
+    limit: Limit,
+    // The heuristic and the cost of the cheapest open tile it is scaled by.
+    heuristic: (Heuristic, i32),
+    // The most steps or the highest f cost a branch may reach in this iteration, `None` until
+    // the first one starts.
+    threshold: Option<i32>,
+    stack: Vec<Frame>,
+    // The fewest steps or the lowest g cost every node was reached with in this iteration. A node
+    // that is reached again without doing better is skipped, which also keeps branches from
+    // running in circles.
+    reached: HashMap<Position, i32>,
+    // The fewest steps or the lowest g cost every node was cut off at by the threshold.
+    cut_off: HashMap<Position, i32>,
+    current: Option<Position>,
+    status: SearchStatus,
+    path: Vec<Position>,
+    cost: Option<i32>,
+    nodes_expanded: usize,
+}
+
+impl DepthFirstSearch {
+    #[must_use]
+    pub fn new(start: Position, goal: Position) -> Self {
+        DepthFirstSearch::with_limit(start, goal, Limit::None, (Heuristic::Zero, 1))
+    }
+
+    /// Iterative deepening DFS, which finds the path with the fewest steps, though not
+    /// necessarily the cheapest one on a grid with costs.
+    #[must_use]
+    pub fn iterative_deepening(start: Position, goal: Position) -> Self {
+        DepthFirstSearch::with_limit(start, goal, Limit::Steps, (Heuristic::Zero, 1))
+    }
+
+    /// IDA*, which finds the cheapest path as long as the heuristic never overestimates.
+    #[must_use]
+    pub fn ida_star(start: Position, goal: Position, heuristic: (Heuristic, i32)) -> Self {
+        DepthFirstSearch::with_limit(start, goal, Limit::Cost, heuristic)
+    }
+
+    fn with_limit(
+        start: Position,
+        goal: Position,
+        limit: Limit,
+        heuristic: (Heuristic, i32),
+    ) -> Self {
+        DepthFirstSearch {
+            start,
+            goal,
+            limit,
+            heuristic,
+            threshold: None,
+            stack: Vec::new(),
+            reached: HashMap::new(),
+            cut_off: HashMap::new(),
+            current: None,
+            status: SearchStatus::Searching,
+            path: Vec::new(),
+            cost: None,
+            nodes_expanded: 0,
+        }
+    }
+
+    fn heuristic(&self, position: Position) -> i32 {
+        let (heuristic, min_cost) = self.heuristic;
+        heuristic.estimate(position, self.goal, min_cost)
+    }
+
+    // The step count or f cost a node reached with `value` steps or g cost is limited by.
+    fn bound(&self, position: Position, value: i32) -> Option<i32> {
+        match self.limit {
+            Limit::None => None,
+            Limit::Steps => Some(value),
+            Limit::Cost => Some(value + self.heuristic(position)),
+        }
+    }
+
+    // Starts over from the start with the next limit, or gives up once there is none.
+    fn start_iteration(&mut self, grid: &Grid) -> SearchStatus {
+        let threshold = if self.threshold.is_none() {
+            self.bound(self.start, 0).unwrap_or_default()
+        } else {
+            // Nodes that were cut off but later reached at least as well don't need a higher
+            // limit, so once every node was, the whole region has been searched.
+            let next_threshold = self
+                .cut_off
+                .iter()
+                .filter(|(position, value)| {
+                    self.reached.get(position).is_none_or(|best| *value < best)
+                })
+                .filter_map(|(position, value)| self.bound(*position, *value))
+                .min();
+            let Some(next_threshold) = next_threshold else {
+                self.current = None;
+                self.status = SearchStatus::NotFound;
+                return self.status;
+            };
+            next_threshold
+        };
+        self.threshold = Some(threshold);
+        self.reached.clear();
+        self.cut_off.clear();
+        self.expand(self.start, 0, 0, grid)
+    }
+
+    fn expand(
+        &mut self,
+        position: Position,
+        g_cost: i32,
+        reached: i32,
+        grid: &Grid,
+    ) -> SearchStatus {
+        self.reached.insert(position, reached);
+        self.current = Some(position);
+        self.nodes_expanded += 1;
+        if position == self.goal {
+            self.path = self.stack.iter().map(|frame| frame.position).collect();
+            self.path.push(position);
+            self.cost = Some(g_cost);
+            self.status = SearchStatus::Found;
+            return self.status;
+        }
+        self.stack.push(Frame {
+            position,
+            g_cost,
+            successors: grid.get_successors(&position),
+            next: 0,
+        });
+        self.status
+    }
+}
+
+impl Search for DepthFirstSearch {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        // Backtracks until a branch is found that may still be followed.
+        loop {
+            let depth = i32::try_from(self.stack.len()).unwrap_or(i32::MAX);
+            let Some(frame) = self.stack.last_mut() else {
+                return self.start_iteration(grid);
+            };
+            let Some(&successor) = frame.successors.get(frame.next) else {
+                self.stack.pop();
+                continue;
+            };
+            frame.next += 1;
+            let g_cost = frame.g_cost + successor.cost;
+            let reached = match self.limit {
+                Limit::None | Limit::Steps => depth,
+                Limit::Cost => g_cost,
+            };
+            if self
+                .reached
+                .get(&successor.position)
+                .is_some_and(|&best| self.limit == Limit::None || best <= reached)
+            {
+                continue;
+            }
+            if self
+                .bound(successor.position, reached)
+                .is_some_and(|bound| Some(bound) > self.threshold)
+            {
+                let cut_off = self.cut_off.entry(successor.position).or_insert(reached);
+                *cut_off = (*cut_off).min(reached);
+                continue;
+            }
+            return self.expand(successor.position, g_cost, reached, grid);
+        }
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    // The successors on the current branch that are still left to try.
+    fn open_set(&self) -> Vec<Position> {
+        let open: HashSet<Position> = self
+            .stack
+            .iter()
+            .flat_map(|frame| &frame.successors[frame.next..])
+            .map(|successor| successor.position)
+            .filter(|position| !self.reached.contains_key(position))
+            .collect();
+        open.into_iter().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.reached.keys().copied().collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.path.clone()
+    }
+
+    fn cost(&self) -> Option<i32> {
+        self.cost
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        // Only the nodes on the current branch have a g cost, the rest were left behind.
+        if self.status == SearchStatus::Found && position == self.goal {
+            return self.cost;
+        }
+        self.stack
+            .iter()
+            .rev()
+            .find(|frame| frame.position == position)
+            .map(|frame| frame.g_cost)
+    }
+}
+
+// References
+// 1. Depth-first search
+// https://en.wikipedia.org/wiki/Depth-first_search
+// 2. Iterative deepening depth-first search
+// https://en.wikipedia.org/wiki/Iterative_deepening_depth-first_search
+// 3. Depth-First Iterative-Deepening: An Optimal Admissible Tree Search, Richard Korf (IDA*)
+// https://en.wikipedia.org/wiki/Iterative_deepening_A*
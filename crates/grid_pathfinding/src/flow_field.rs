@@ -0,0 +1,194 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+use super::{Grid, Position, Search, SearchStatus};
+
+/// A flow field stores, for every tile that can reach the goal, the direction of the first step
+/// of a cheapest path there. It is built by a single Dijkstra pass outwards from the goal, after
+/// which any number of units heading to the same goal can look up their next step in constant
+/// time.
+///
+/// As a `Search` it integrates one tile per step, and its path is the one followed from `start`
+/// once the whole grid has been integrated.
+#[derive(Debug)]
+pub struct FlowField {
+    width: i32,
+    height: i32,
+    start: Position,
+    // The cost of the cheapest path from each tile to the goal.
+    integration: Vec<Option<i32>>,
+    directions: Vec<Option<(i32, i32)>>,
+    open: BinaryHeap<Reverse<(i32, Position)>>,
+    closed: Vec<bool>,
+    current: Option<Position>,
+    status: SearchStatus,
+    nodes_expanded: usize,
+}
+
+impl FlowField {
+    #[must_use]
+    pub fn new(grid: &Grid, start: Position, goal: Position) -> Self {
+        let tiles = grid.blocked.len();
+        let mut flow_field = FlowField {
+            width: grid.width,
+            height: grid.height,
+            start,
+            integration: vec![None; tiles],
+            directions: vec![None; tiles],
+            open: BinaryHeap::new(),
+            closed: vec![false; tiles],
+            current: None,
+            status: SearchStatus::Searching,
+            nodes_expanded: 0,
+        };
+        // Nothing can reach a blocked goal, so there is no field to build.
+        if !grid.is_walkable(goal.0, goal.1) {
+            flow_field.status = SearchStatus::NotFound;
+            return flow_field;
+        }
+        let goal_index = flow_field.index(goal);
+        flow_field.integration[goal_index] = Some(0);
+        flow_field.directions[goal_index] = Some((0, 0));
+        flow_field.open.push(Reverse((0, goal)));
+        flow_field
+    }
+
+    /// The direction to step in from `position` to get closer to the goal, `(0, 0)` at the goal
+    /// and `None` where the goal can't be reached.
+    #[must_use]
+    pub fn direction(&self, position: Position) -> Option<(i32, i32)> {
+        self.in_bounds(position)
+            .then(|| self.directions[self.index(position)])
+            .flatten()
+    }
+
+    /// The cost of the cheapest path from `position` to the goal.
+    #[must_use]
+    pub fn cost_to_goal(&self, position: Position) -> Option<i32> {
+        self.in_bounds(position)
+            .then(|| self.integration[self.index(position)])
+            .flatten()
+    }
+
+    /// Follows the field from `position` to the goal.
+    #[must_use]
+    pub fn path_from(&self, position: Position) -> Vec<Position> {
+        let mut path = Vec::new();
+        let mut position = position;
+        while let Some(direction) = self.direction(position) {
+            path.push(position);
+            if direction == (0, 0) || path.len() > self.directions.len() {
+                return path;
+            }
+            position = Position(position.0 + direction.0, position.1 + direction.1);
+        }
+        Vec::new()
+    }
+
+    fn in_bounds(&self, position: Position) -> bool {
+        position.0 >= 0 && position.0 < self.width && position.1 >= 0 && position.1 < self.height
+    }
+
+    fn index(&self, position: Position) -> usize {
+        usize::try_from(position.1 * self.width + position.0)
+            .expect("the position should be inside the grid")
+    }
+}
+
+impl Search for FlowField {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        let position = loop {
+            let Some(Reverse((_, position))) = self.open.pop() else {
+                self.current = None;
+                self.status = if self.cost_to_goal(self.start).is_some() {
+                    SearchStatus::Found
+                } else {
+                    SearchStatus::NotFound
+                };
+                return self.status;
+            };
+            if !self.closed[self.index(position)] {
+                break position;
+            }
+        };
+        let index = self.index(position);
+        self.closed[index] = true;
+        self.current = Some(position);
+        self.nodes_expanded += 1;
+
+        // Every neighbor this tile can step onto can step back onto it, paying its cost.
+        for successor in grid.get_successors(&position) {
+            let neighbor = successor.position;
+            let neighbor_index = self.index(neighbor);
+            let cost_to_goal =
+                self.integration[index].unwrap_or_default() + grid.step_cost(neighbor, position);
+            if self.integration[neighbor_index].is_none_or(|old_cost| cost_to_goal < old_cost) {
+                self.integration[neighbor_index] = Some(cost_to_goal);
+                self.directions[neighbor_index] =
+                    Some((position.0 - neighbor.0, position.1 - neighbor.1));
+                self.open.push(Reverse((cost_to_goal, neighbor)));
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        let open: HashSet<Position> = self
+            .open
+            .iter()
+            .map(|Reverse((_, position))| *position)
+            .filter(|position| !self.closed[self.index(*position)])
+            .collect();
+        open.into_iter().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Position(x, y)))
+            .filter(|position| self.closed[self.index(*position)])
+            .collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        if self.status == SearchStatus::Found {
+            self.path_from(self.start)
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn cost(&self) -> Option<i32> {
+        if self.status == SearchStatus::Found {
+            self.cost_to_goal(self.start)
+        } else {
+            None
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.cost_to_goal(position)
+    }
+
+    fn direction(&self, position: Position) -> Option<(i32, i32)> {
+        FlowField::direction(self, position)
+    }
+}
+
+// References
+// 1. Flow Field Pathfinding, Leif Erkenbrach
+// https://leifnode.com/2013/12/flow-field-pathfinding/
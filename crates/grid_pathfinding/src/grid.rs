@@ -0,0 +1,476 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{TerrainId, TerrainRegistry};
+
+/// Step costs are kept in hundredths of a tile, so that a diagonal step can cost about √2 times
+/// a straight one in whole numbers: stepping onto a tile costs its cost times one of these.
+pub const STRAIGHT_STEP_COST: i32 = 100;
+pub const DIAGONAL_STEP_COST: i32 = 141;
+
+/// Turns a cost worked out with floats back into a whole cost, dropping the fraction. Callers
+/// round first where that is wanted, and costs beyond `i32` saturate at its limits.
+#[allow(
+    clippy::cast_possible_truncation,
+    reason = "the fraction is meant to be dropped and `as` saturates instead of wrapping"
+)]
+pub(crate) fn whole_cost(cost: f64) -> i32 {
+    cost as i32
+}
+
+/// A grid of tiles, each of them either blocked or open with a cost for stepping onto it.
+/// `terrain` says what every tile is made of, the searches only look at `costs` and `blocked`.
+/// Edit the costs with `set_terrain` and `set_cost`, which keep them in step with the terrain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid {
+    pub width: i32,
+    pub height: i32,
+    pub costs: Vec<Option<i32>>,
+    pub blocked: Vec<bool>,
+    pub terrain: Vec<TerrainId>,
+    /// Costs given to single tiles with `set_cost`, which replace the cost of their terrain until
+    /// another terrain is painted over them.
+    pub cost_overrides: Vec<Option<i32>>,
+    pub diagonal_policy: DiagonalPolicy,
+}
+
+impl Grid {
+    /// An open grid where every tile costs 1. A negative width or height makes an empty grid.
+    #[must_use]
+    pub fn new(width: i32, height: i32, diagonal_policy: DiagonalPolicy) -> Grid {
+        let tiles = usize::try_from(width).unwrap_or_default()
+            * usize::try_from(height).unwrap_or_default();
+        Grid {
+            width,
+            height,
+            costs: vec![Some(1); tiles],
+            blocked: vec![false; tiles],
+            terrain: vec![TerrainId::FLOOR; tiles],
+            cost_overrides: vec![None; tiles],
+            diagonal_policy,
+        }
+    }
+
+    /// A copy of the grid with a new size, keeping the tiles that are inside both sizes.
+    #[must_use]
+    pub fn resized(&self, width: i32, height: i32) -> Grid {
+        let mut grid = Grid::new(width, height, self.diagonal_policy);
+        for y in 0..height.min(self.height) {
+            for x in 0..width.min(self.width) {
+                let index = grid.xy_idx(x, y);
+                let previous_index = self.xy_idx(x, y);
+                grid.costs[index] = self.costs[previous_index];
+                grid.blocked[index] = self.blocked[previous_index];
+                grid.terrain[index] = self.terrain[previous_index];
+                grid.cost_overrides[index] = self.cost_overrides[previous_index];
+            }
+        }
+        grid
+    }
+
+    /// Paints a tile with a terrain, taking its cost and whether it's blocked from the terrain,
+    /// and drops the cost set with `set_cost`. Returns false and leaves the tile alone if the
+    /// terrain isn't in the registry.
+    pub fn set_terrain(
+        &mut self,
+        position: Position,
+        id: TerrainId,
+        terrains: &TerrainRegistry,
+    ) -> bool {
+        let Some(terrain) = terrains.get(id) else {
+            return false;
+        };
+        let index = self.xy_idx(position.0, position.1);
+        self.terrain[index] = id;
+        self.costs[index] = Some(terrain.cost);
+        self.cost_overrides[index] = None;
+        self.blocked[index] = !terrain.passable;
+        true
+    }
+
+    /// Gives a tile its own cost instead of the cost of its terrain, e.g. for noise or costs
+    /// edited by hand. `MovementProfile`s scale it like the cost of the terrain.
+    pub fn set_cost(&mut self, position: Position, cost: i32) {
+        let index = self.xy_idx(position.0, position.1);
+        self.costs[index] = Some(cost);
+        self.cost_overrides[index] = Some(cost);
+    }
+
+    /// Takes the cost of every tile from its override, or else from its terrain, e.g. after the
+    /// costs of the terrains have changed. Tiles whose terrain isn't in the registry keep theirs.
+    pub fn refresh_costs(&mut self, terrains: &TerrainRegistry) {
+        for ((cost, terrain), cost_override) in self
+            .costs
+            .iter_mut()
+            .zip(&self.terrain)
+            .zip(&self.cost_overrides)
+        {
+            if let Some(cost_override) = cost_override {
+                *cost = Some(*cost_override);
+            } else if let Some(terrain) = terrains.get(*terrain) {
+                *cost = Some(terrain.cost);
+            }
+        }
+    }
+
+    /// Turns blocked tiles into walls and open tiles into floor, for maps that were only given
+    /// obstacles. The costs are kept.
+    pub fn reset_terrain(&mut self) {
+        for (terrain, blocked) in self.terrain.iter_mut().zip(&self.blocked) {
+            *terrain = if *blocked {
+                TerrainId::WALL
+            } else {
+                TerrainId::FLOOR
+            };
+        }
+    }
+
+    /// # Panics
+    /// Panics if the tile is out of bounds.
+    #[must_use]
+    pub fn xy_idx(&self, x: i32, y: i32) -> usize {
+        usize::try_from(y * self.width + x).expect("the tile should be inside the grid")
+    }
+
+    #[must_use]
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.width && y >= 0 && y < self.height
+    }
+
+    #[must_use]
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && !self.blocked[self.xy_idx(x, y)]
+    }
+
+    /// The cost of stepping onto a tile, tiles without a cost cost 1 like in `get_successors`.
+    #[must_use]
+    pub fn tile_cost(&self, position: Position) -> i32 {
+        self.costs[self.xy_idx(position.0, position.1)].unwrap_or(1)
+    }
+
+    #[must_use]
+    pub fn allows_diagonals(&self) -> bool {
+        self.diagonal_policy != DiagonalPolicy::Never
+    }
+
+    /// The cost of a single straight or diagonal step between two neighboring tiles, the same
+    /// as `get_successors` gives it.
+    #[must_use]
+    pub fn step_cost(&self, from: Position, to: Position) -> i32 {
+        let step_cost = if from.0 != to.0 && from.1 != to.1 {
+            DIAGONAL_STEP_COST
+        } else {
+            STRAIGHT_STEP_COST
+        };
+        self.tile_cost(to) * step_cost
+    }
+
+    /// The cost of following a path of single steps, like `step_cost` gives them.
+    #[must_use]
+    pub fn path_cost(&self, path: &[Position]) -> i32 {
+        path.windows(2)
+            .map(|step| self.step_cost(step[0], step[1]))
+            .sum()
+    }
+
+    /// Checks whether a single step from `from` in `direction` is allowed: the tile it steps onto
+    /// has to be open, and a diagonal step has to follow the diagonal policy.
+    #[must_use]
+    pub fn can_step(&self, from: Position, direction: (i32, i32)) -> bool {
+        let (dx, dy) = direction;
+        if !self.is_walkable(from.0 + dx, from.1 + dy) {
+            return false;
+        }
+        if dx == 0 || dy == 0 {
+            return true;
+        }
+        self.allows_diagonals() && self.corner_passable(from, direction)
+    }
+
+    // Whether a diagonal move may pass between the two tiles beside it. Without diagonals, the
+    // corner is treated like with `OnlyWhenNoObstacles`, which `line_of_sight` relies on.
+    fn corner_passable(&self, from: Position, direction: (i32, i32)) -> bool {
+        let (dx, dy) = direction;
+        let horizontal_open = self.is_walkable(from.0 + dx, from.1);
+        let vertical_open = self.is_walkable(from.0, from.1 + dy);
+        match self.diagonal_policy {
+            DiagonalPolicy::Always => true,
+            DiagonalPolicy::NoCornerCutting => horizontal_open || vertical_open,
+            DiagonalPolicy::Never | DiagonalPolicy::OnlyWhenNoObstacles => {
+                horizontal_open && vertical_open
+            }
+        }
+    }
+
+    /// Checks whether a straight line between the centers of two tiles only crosses open tiles.
+    /// Passing exactly through a corner follows the diagonal policy: without diagonals both tiles
+    /// touching that corner need to be open as well.
+    #[must_use]
+    pub fn line_of_sight(&self, from: Position, to: Position) -> bool {
+        let dx = (to.0 - from.0).abs();
+        let dy = (to.1 - from.1).abs();
+        let x_step = (to.0 - from.0).signum();
+        let y_step = (to.1 - from.1).signum();
+        let (mut x, mut y) = (from.0, from.1);
+        let mut error = dx - dy;
+        let mut remaining = dx + dy;
+        loop {
+            if !self.is_walkable(x, y) {
+                return false;
+            }
+            if remaining <= 0 {
+                break;
+            }
+            match error.cmp(&0) {
+                Ordering::Greater => {
+                    x += x_step;
+                    error -= 2 * dy;
+                    remaining -= 1;
+                }
+                Ordering::Less => {
+                    y += y_step;
+                    error += 2 * dx;
+                    remaining -= 1;
+                }
+                // The line passes exactly through a corner.
+                Ordering::Equal => {
+                    if !self.corner_passable(Position(x, y), (x_step, y_step)) {
+                        return false;
+                    }
+                    x += x_step;
+                    y += y_step;
+                    error += 2 * (dx - dy);
+                    remaining -= 2;
+                }
+            }
+        }
+        true
+    }
+
+    /// Lists the tiles whose cost, blocked state or terrain differs from an earlier copy of this
+    /// grid.
+    /// Every tile counts as changed if the size or the diagonal policy is different.
+    #[must_use]
+    pub fn changed_tiles(&self, previous: &Grid) -> Vec<Position> {
+        let resized = self.width != previous.width || self.height != previous.height;
+        let mut changed_tiles = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = self.xy_idx(x, y);
+                if resized
+                    || self.diagonal_policy != previous.diagonal_policy
+                    || self.blocked[index] != previous.blocked[index]
+                    || self.costs[index] != previous.costs[index]
+                    || self.terrain[index] != previous.terrain[index]
+                {
+                    changed_tiles.push(Position(x, y));
+                }
+            }
+        }
+        changed_tiles
+    }
+
+    /// Returns the cost shared by every open tile.
+    ///
+    /// # Errors
+    /// Returns the lowest and highest cost when the open tiles don't all cost the same.
+    pub fn uniform_cost(&self) -> Result<i32, (i32, i32)> {
+        let mut open_costs = self
+            .costs
+            .iter()
+            .zip(&self.blocked)
+            .filter(|(_, blocked)| !**blocked)
+            .map(|(cost, _)| cost.unwrap_or(1));
+        let Some(first) = open_costs.next() else {
+            return Ok(1);
+        };
+        let (min, max) = open_costs.fold((first, first), |(min, max), cost| {
+            (min.min(cost), max.max(cost))
+        });
+        if min == max {
+            Ok(min)
+        } else {
+            Err((min, max))
+        }
+    }
+
+    /// The lowest cost of any open tile, or 1 if every tile is blocked.
+    #[must_use]
+    pub fn min_cost(&self) -> i32 {
+        self.costs
+            .iter()
+            .zip(&self.blocked)
+            .filter(|(_, blocked)| !**blocked)
+            .map(|(cost, _)| cost.unwrap_or(1))
+            .min()
+            .unwrap_or(1)
+    }
+
+    /// The open tiles a single step away, with the cost of stepping onto each of them.
+    #[must_use]
+    pub fn get_successors(&self, position: &Position) -> Vec<Successor> {
+        let mut successors = Vec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                } // Exclude current position.
+                if !self.can_step(*position, (dx, dy)) {
+                    continue;
+                } // Exclude blocked tiles and diagonals the policy doesn't allow.
+                let neighbor_position = Position(position.0 + dx, position.1 + dy);
+                successors.push(Successor {
+                    position: neighbor_position,
+                    cost: self.step_cost(*position, neighbor_position),
+                });
+            }
+        }
+        successors
+    }
+}
+
+/// Whether a step may go diagonally, and which blocked tiles beside the step stop it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DiagonalPolicy {
+    #[default]
+    Never,
+    /// Diagonal steps may even squeeze between two blocked tiles that touch at a corner.
+    Always,
+    /// Diagonal steps may pass one blocked tile, but not squeeze between two.
+    NoCornerCutting,
+    /// Diagonal steps need both tiles beside them to be open, like on the Moving AI benchmarks.
+    OnlyWhenNoObstacles,
+}
+
+impl DiagonalPolicy {
+    pub const ALL: [DiagonalPolicy; 4] = [
+        DiagonalPolicy::Never,
+        DiagonalPolicy::Always,
+        DiagonalPolicy::NoCornerCutting,
+        DiagonalPolicy::OnlyWhenNoObstacles,
+    ];
+}
+
+impl fmt::Display for DiagonalPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiagonalPolicy::Never => "Never",
+            DiagonalPolicy::Always => "Always",
+            DiagonalPolicy::NoCornerCutting => "No Corner Cutting",
+            DiagonalPolicy::OnlyWhenNoObstacles => "Only When No Obstacles",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Position(pub i32, pub i32);
+
+impl Position {
+    /// The Manhattan distance between two positions, saturating at `i32::MAX`.
+    #[must_use]
+    pub fn distance(&self, other: &Position) -> i32 {
+        i32::try_from(self.0.abs_diff(other.0) + self.1.abs_diff(other.1)).unwrap_or(i32::MAX)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd)]
+pub struct Successor {
+    pub position: Position,
+    pub cost: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The diagonal neighbors of the center of a 3x3 grid, after blocking the given tiles.
+    fn diagonal_successors(diagonal_policy: DiagonalPolicy, blocked: &[Position]) -> Vec<Position> {
+        let mut grid = Grid::new(3, 3, diagonal_policy);
+        for position in blocked {
+            let index = grid.xy_idx(position.0, position.1);
+            grid.blocked[index] = true;
+        }
+        grid.get_successors(&Position(1, 1))
+            .into_iter()
+            .map(|successor| successor.position)
+            .filter(|position| position.0 != 1 && position.1 != 1)
+            .collect()
+    }
+
+    #[test]
+    fn never_has_no_diagonal_steps() {
+        assert!(diagonal_successors(DiagonalPolicy::Never, &[]).is_empty());
+        let grid = Grid::new(3, 3, DiagonalPolicy::Never);
+        assert_eq!(grid.get_successors(&Position(1, 1)).len(), 4);
+    }
+
+    #[test]
+    fn always_squeezes_between_blocked_tiles() {
+        let blocked = [Position(1, 0), Position(2, 1)];
+        let successors = diagonal_successors(DiagonalPolicy::Always, &blocked);
+        assert!(successors.contains(&Position(2, 0)));
+        assert_eq!(successors.len(), 4);
+    }
+
+    #[test]
+    fn no_corner_cutting_passes_one_blocked_tile() {
+        let successors = diagonal_successors(DiagonalPolicy::NoCornerCutting, &[Position(1, 0)]);
+        assert!(successors.contains(&Position(2, 0)));
+        assert!(successors.contains(&Position(0, 0)));
+
+        let blocked = [Position(1, 0), Position(2, 1)];
+        let successors = diagonal_successors(DiagonalPolicy::NoCornerCutting, &blocked);
+        assert!(!successors.contains(&Position(2, 0)));
+        assert_eq!(successors.len(), 3);
+    }
+
+    #[test]
+    fn only_when_no_obstacles_needs_both_tiles_open() {
+        let successors =
+            diagonal_successors(DiagonalPolicy::OnlyWhenNoObstacles, &[Position(1, 0)]);
+        assert_eq!(successors, vec![Position(0, 2), Position(2, 2)]);
+    }
+
+    #[test]
+    fn diagonal_steps_cost_more() {
+        let mut grid = Grid::new(3, 3, DiagonalPolicy::Always);
+        let index = grid.xy_idx(2, 2);
+        grid.costs[index] = Some(3);
+        assert_eq!(
+            grid.step_cost(Position(1, 1), Position(2, 1)),
+            STRAIGHT_STEP_COST
+        );
+        assert_eq!(
+            grid.step_cost(Position(1, 1), Position(0, 0)),
+            DIAGONAL_STEP_COST
+        );
+        assert_eq!(
+            grid.step_cost(Position(1, 1), Position(2, 2)),
+            3 * DIAGONAL_STEP_COST
+        );
+    }
+
+    #[test]
+    fn line_of_sight_through_a_corner_follows_the_policy() {
+        for (diagonal_policy, visible) in [
+            (DiagonalPolicy::Never, false),
+            (DiagonalPolicy::Always, true),
+            (DiagonalPolicy::NoCornerCutting, true),
+            (DiagonalPolicy::OnlyWhenNoObstacles, false),
+        ] {
+            let mut grid = Grid::new(3, 3, diagonal_policy);
+            let index = grid.xy_idx(1, 0);
+            grid.blocked[index] = true;
+            assert_eq!(
+                grid.line_of_sight(Position(0, 0), Position(2, 2)),
+                visible,
+                "{diagonal_policy}"
+            );
+        }
+    }
+}
+
+// References
+// 1. PathFinding.js diagonal movement options
+// https://github.com/qiao/PathFinding.js/blob/master/src/core/DiagonalMovement.js
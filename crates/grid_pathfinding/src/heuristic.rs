@@ -0,0 +1,139 @@
+use std::f64::consts::SQRT_2;
+use std::fmt;
+
+use super::{
+    whole_cost, Algorithm, DiagonalPolicy, Grid, PathError, PathfindingAlgorithm, Position, Search,
+    DIAGONAL_STEP_COST, STRAIGHT_STEP_COST,
+};
+
+/// Estimates the cost from a tile to the goal for A* and the other searches that follow one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Heuristic {
+    /// Straight steps only, which overestimates once diagonal steps are allowed.
+    Manhattan,
+    /// Straight and diagonal steps, the exact distance on an open grid with diagonals.
+    Octile,
+    /// The straight line distance.
+    Euclidean,
+    /// Diagonal steps that cost as much as straight ones.
+    Chebyshev,
+    /// No estimate at all, which turns A* into Dijkstra.
+    Zero,
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 5] = [
+        Heuristic::Manhattan,
+        Heuristic::Octile,
+        Heuristic::Euclidean,
+        Heuristic::Chebyshev,
+        Heuristic::Zero,
+    ];
+
+    /// The most informed heuristic that never overestimates on this grid.
+    #[must_use]
+    pub fn for_grid(grid: &Grid) -> Heuristic {
+        if grid.allows_diagonals() {
+            Heuristic::Octile
+        } else {
+            Heuristic::Manhattan
+        }
+    }
+
+    /// The estimated cost from `from` to `to` in hundredths of a tile, when every tile costs at
+    /// least `min_cost`. Scaling by the cheapest open tile makes the estimate closer on terrain
+    /// where nothing costs 1, without ever overestimating.
+    #[must_use]
+    pub fn estimate(self, from: Position, to: Position, min_cost: i32) -> i32 {
+        let dx = (to.0 - from.0).abs();
+        let dy = (to.1 - from.1).abs();
+        let distance = match self {
+            Heuristic::Manhattan => (dx + dy) * STRAIGHT_STEP_COST,
+            Heuristic::Octile => {
+                dx.max(dy) * STRAIGHT_STEP_COST
+                    + dx.min(dy) * (DIAGONAL_STEP_COST - STRAIGHT_STEP_COST)
+            }
+            // A diagonal step costs a little less than √2 after rounding, so the distance is
+            // shrunk to match, or it would overestimate long diagonals.
+            Heuristic::Euclidean => {
+                let length = f64::from(dx * dx + dy * dy).sqrt();
+                whole_cost(length * f64::from(DIAGONAL_STEP_COST) / SQRT_2)
+            }
+            Heuristic::Chebyshev => dx.max(dy) * STRAIGHT_STEP_COST,
+            Heuristic::Zero => 0,
+        };
+        distance * min_cost
+    }
+
+    /// Checks whether the heuristic never drops by more than the cost of a step under the
+    /// diagonal policy, which A* needs to find the cheapest path.
+    #[must_use]
+    pub fn is_consistent(self, diagonal_policy: DiagonalPolicy) -> bool {
+        self != Heuristic::Manhattan || diagonal_policy == DiagonalPolicy::Never
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Heuristic::Manhattan => "Manhattan",
+            Heuristic::Octile => "Octile",
+            Heuristic::Euclidean => "Euclidean",
+            Heuristic::Chebyshev => "Chebyshev",
+            Heuristic::Zero => "Zero",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How many times the heuristic counts towards the f cost of weighted A* and the first path of
+/// ARA*, unless chosen otherwise.
+pub const DEFAULT_WEIGHT: f64 = 2.0;
+
+/// Searches with a chosen heuristic, instead of the one `Heuristic::for_grid` picks. Algorithms
+/// that don't follow a heuristic ignore it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeuristicAlgorithm {
+    pub pathfinding_algorithm: PathfindingAlgorithm,
+    pub heuristic: Heuristic,
+    /// The epsilon of weighted A* and ARA*, paths cost at most this many times the cheapest one.
+    pub weight: f64,
+}
+
+impl HeuristicAlgorithm {
+    /// Whether the algorithm follows the heuristic at all.
+    #[must_use]
+    pub fn uses_heuristic(&self) -> bool {
+        matches!(
+            self.pathfinding_algorithm,
+            PathfindingAlgorithm::AStar
+                | PathfindingAlgorithm::BidirectionalAStar
+                | PathfindingAlgorithm::JumpPointSearch
+                | PathfindingAlgorithm::WeightedAStar
+                | PathfindingAlgorithm::ARAStar
+                | PathfindingAlgorithm::GreedyBestFirst
+                | PathfindingAlgorithm::IDAStar
+        )
+    }
+}
+
+impl Algorithm for HeuristicAlgorithm {
+    fn start_search(
+        &self,
+        grid: &Grid,
+        start: Position,
+        goal: Position,
+    ) -> Result<Box<dyn Search>, PathError> {
+        self.pathfinding_algorithm.start_search_with_heuristic(
+            grid,
+            start,
+            goal,
+            self.heuristic,
+            self.weight,
+        )
+    }
+}
+
+// References
+// 1. Amit Patel's heuristics for grid maps
+// http://theory.stanford.edu/~amitp/GameProgramming/Heuristics.html
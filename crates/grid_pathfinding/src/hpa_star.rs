@@ -0,0 +1,650 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+
+use super::{
+    Algorithm, Grid, PathError, Position, Search, SearchStatus, Successor, STRAIGHT_STEP_COST,
+};
+
+pub const CLUSTER_SIZE: i32 = 8;
+
+// Entrances at least this wide get a transition at each end instead of a single one in the middle.
+const MAX_ENTRANCE_WIDTH: usize = 6;
+
+// Splits the grid into square clusters, the last row and column may be smaller.
+#[derive(Clone, Copy, Debug)]
+struct Clustering {
+    cluster_size: i32,
+    width: i32,
+    height: i32,
+    clusters_wide: i32,
+    clusters_high: i32,
+}
+
+impl Clustering {
+    fn new(grid: &Grid, cluster_size: i32) -> Self {
+        Clustering {
+            cluster_size,
+            width: grid.width,
+            height: grid.height,
+            clusters_wide: (grid.width + cluster_size - 1) / cluster_size,
+            clusters_high: (grid.height + cluster_size - 1) / cluster_size,
+        }
+    }
+
+    fn len(self) -> usize {
+        self.cluster_at(0, self.clusters_high)
+    }
+
+    fn cluster_of(self, position: Position) -> usize {
+        self.cluster_at(
+            position.0 / self.cluster_size,
+            position.1 / self.cluster_size,
+        )
+    }
+
+    // Clusters are numbered row by row, like the tiles of a grid.
+    fn cluster_at(self, cx: i32, cy: i32) -> usize {
+        usize::try_from(cy * self.clusters_wide + cx).expect("the cluster should be in the grid")
+    }
+
+    fn coordinates(self, cluster: usize) -> (i32, i32) {
+        let cluster = i32::try_from(cluster).expect("the cluster should be in the grid");
+        (cluster % self.clusters_wide, cluster / self.clusters_wide)
+    }
+
+    fn bounds(self, cluster: usize) -> Bounds {
+        let (cx, cy) = self.coordinates(cluster);
+        Bounds {
+            min: Position(cx * self.cluster_size, cy * self.cluster_size),
+            max: Position(
+                ((cx + 1) * self.cluster_size).min(self.width),
+                ((cy + 1) * self.cluster_size).min(self.height),
+            ),
+        }
+    }
+
+    // Clusters sharing an edge or a corner with `cluster`.
+    fn neighbors(self, cluster: usize) -> Vec<usize> {
+        let (cx, cy) = self.coordinates(cluster);
+        let mut neighbors = Vec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (x, y) = (cx + dx, cy + dy);
+                if (dx, dy) != (0, 0)
+                    && x >= 0
+                    && x < self.clusters_wide
+                    && y >= 0
+                    && y < self.clusters_high
+                {
+                    neighbors.push(self.cluster_at(x, y));
+                }
+            }
+        }
+        neighbors
+    }
+}
+
+// The tiles of a cluster, `max` is exclusive.
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    min: Position,
+    max: Position,
+}
+
+impl Bounds {
+    fn contains(self, position: Position) -> bool {
+        position.0 >= self.min.0
+            && position.0 < self.max.0
+            && position.1 >= self.min.1
+            && position.1 < self.max.1
+    }
+}
+
+// The edges of the abstract graph leaving the transition tiles of one cluster: the cheapest
+// paths to the other transitions inside the cluster, and the steps into neighboring clusters.
+#[derive(Debug, Default)]
+struct Cluster {
+    edges: HashMap<Position, Vec<Successor>>,
+}
+
+/// HPA* divides the grid into clusters and connects them through entrances along their borders.
+/// Searching this abstract graph and refining the result inside each cluster is much cheaper
+/// than searching every tile of a large grid, at the price of paths that are close to, but not
+/// always, the shortest.
+#[derive(Clone, Debug)]
+pub struct HierarchicalMap {
+    clustering: Clustering,
+    // The grid the clusters were last built from.
+    grid: Grid,
+    // Transition tile pairs between two neighboring clusters, keyed by the cluster indices in
+    // ascending order, with the first tile of each pair in the first cluster.
+    borders: HashMap<(usize, usize), Vec<(Position, Position)>>,
+    clusters: Vec<Arc<Cluster>>,
+}
+
+impl HierarchicalMap {
+    #[must_use]
+    pub fn new(grid: &Grid, cluster_size: i32) -> Self {
+        let clustering = Clustering::new(grid, cluster_size);
+        let mut hierarchical_map = HierarchicalMap {
+            clustering,
+            grid: grid.clone(),
+            borders: HashMap::new(),
+            clusters: Vec::new(),
+        };
+        for cluster in 0..clustering.len() {
+            for neighbor in clustering.neighbors(cluster) {
+                if cluster < neighbor {
+                    let transitions = hierarchical_map.border_transitions(cluster, neighbor);
+                    hierarchical_map
+                        .borders
+                        .insert((cluster, neighbor), transitions);
+                }
+            }
+        }
+        hierarchical_map.clusters = (0..clustering.len())
+            .map(|cluster| Arc::new(hierarchical_map.build_cluster(cluster)))
+            .collect();
+        hierarchical_map
+    }
+
+    #[must_use]
+    pub fn cluster_count(&self) -> usize {
+        self.clusters.len()
+    }
+
+    /// Rebuilds the clusters whose tiles changed since the last update, along with any neighbor
+    /// whose entrances changed with them, and returns how many clusters were rebuilt.
+    pub fn update(&mut self, grid: &Grid) -> usize {
+        if grid.width != self.grid.width
+            || grid.height != self.grid.height
+            || grid.diagonal_policy != self.grid.diagonal_policy
+        {
+            *self = HierarchicalMap::new(grid, self.clustering.cluster_size);
+            return self.clusters.len();
+        }
+        let changed_tiles = grid.changed_tiles(&self.grid);
+        if changed_tiles.is_empty() {
+            return 0;
+        }
+        self.grid = grid.clone();
+
+        let changed_tiles: HashSet<Position> = changed_tiles.into_iter().collect();
+        let touched_clusters: HashSet<usize> = changed_tiles
+            .iter()
+            .map(|tile| self.clustering.cluster_of(*tile))
+            .collect();
+        // Diagonal transitions across the corner of a cluster depend on the tiles of the two
+        // clusters beside that corner, so the borders between neighbors are checked as well.
+        let mut borders_to_check = HashSet::new();
+        for &cluster in &touched_clusters {
+            let mut nearby_clusters = self.clustering.neighbors(cluster);
+            nearby_clusters.push(cluster);
+            for &first in &nearby_clusters {
+                for second in self.clustering.neighbors(first) {
+                    if first < second && nearby_clusters.contains(&second) {
+                        borders_to_check.insert((first, second));
+                    }
+                }
+            }
+        }
+        // A cluster also has to be rebuilt when its entrances moved, or when the cost of stepping
+        // through one of them changed.
+        let mut rebuilt_clusters = touched_clusters;
+        for key in borders_to_check {
+            let transitions = self.border_transitions(key.0, key.1);
+            let costs_changed = transitions.iter().any(|(first, second)| {
+                changed_tiles.contains(first) || changed_tiles.contains(second)
+            });
+            if costs_changed || self.borders.get(&key) != Some(&transitions) {
+                rebuilt_clusters.insert(key.0);
+                rebuilt_clusters.insert(key.1);
+                self.borders.insert(key, transitions);
+            }
+        }
+        for &cluster in &rebuilt_clusters {
+            self.clusters[cluster] = Arc::new(self.build_cluster(cluster));
+        }
+        rebuilt_clusters.len()
+    }
+
+    /// Starts a search over the abstract graph, connecting the start and goal to the transitions
+    /// of their clusters first.
+    #[must_use]
+    pub fn new_search(&self, start: Position, goal: Position) -> HierarchicalSearch {
+        let start_cluster = self.clustering.cluster_of(start);
+        let goal_cluster = self.clustering.cluster_of(goal);
+
+        let start_paths =
+            ClusterPaths::new(&self.grid, self.clustering.bounds(start_cluster), start);
+        let mut start_edges: Vec<Successor> = self.clusters[start_cluster]
+            .edges
+            .keys()
+            .filter_map(|node| {
+                start_paths.cost(*node).map(|cost| Successor {
+                    position: *node,
+                    cost,
+                })
+            })
+            .collect();
+        if start_cluster == goal_cluster {
+            if let Some(cost) = start_paths.cost(goal) {
+                start_edges.push(Successor {
+                    position: goal,
+                    cost,
+                });
+            }
+        }
+
+        let goal_bounds = self.clustering.bounds(goal_cluster);
+        let goal_edges = self.clusters[goal_cluster]
+            .edges
+            .keys()
+            .filter_map(|node| {
+                let paths = ClusterPaths::new(&self.grid, goal_bounds, *node);
+                paths.cost(goal).map(|cost| (*node, cost))
+            })
+            .collect();
+
+        HierarchicalSearch::new(
+            self.clustering,
+            self.grid.allows_diagonals(),
+            self.clusters.clone(),
+            start,
+            goal,
+            start_edges,
+            goal_edges,
+        )
+    }
+
+    // Finds the transitions between two neighboring clusters. Every run of open tile pairs across
+    // a shared edge is one entrance. Diagonal steps only need their own transition when both
+    // corner tiles are blocked, otherwise a straight entrance already connects the same tiles.
+    fn border_transitions(&self, first: usize, second: usize) -> Vec<(Position, Position)> {
+        let grid = &self.grid;
+        let first_bounds = self.clustering.bounds(first);
+        let second_bounds = self.clustering.bounds(second);
+        let mut straight = Vec::new();
+        let mut transitions = Vec::new();
+        for y in first_bounds.min.1..first_bounds.max.1 {
+            for x in first_bounds.min.0..first_bounds.max.0 {
+                let on_edge = x == first_bounds.min.0
+                    || x == first_bounds.max.0 - 1
+                    || y == first_bounds.min.1
+                    || y == first_bounds.max.1 - 1;
+                if !on_edge || !grid.is_walkable(x, y) {
+                    continue;
+                }
+                for successor in grid.get_successors(&Position(x, y)) {
+                    let Position(nx, ny) = successor.position;
+                    if !second_bounds.contains(successor.position) {
+                        continue;
+                    }
+                    if nx == x || ny == y {
+                        straight.push((Position(x, y), successor.position));
+                    } else if !grid.is_walkable(nx, y) && !grid.is_walkable(x, ny) {
+                        transitions.push((Position(x, y), successor.position));
+                    }
+                }
+            }
+        }
+
+        straight.sort();
+        let mut entrance: Vec<(Position, Position)> = Vec::new();
+        for pair in straight {
+            if let Some(last) = entrance.last() {
+                if last.0.distance(&pair.0) != 1 {
+                    add_entrance(&mut transitions, &entrance);
+                    entrance.clear();
+                }
+            }
+            entrance.push(pair);
+        }
+        add_entrance(&mut transitions, &entrance);
+        transitions
+    }
+
+    fn build_cluster(&self, cluster: usize) -> Cluster {
+        let mut edges: HashMap<Position, Vec<Successor>> = HashMap::new();
+        for neighbor in self.clustering.neighbors(cluster) {
+            let key = (cluster.min(neighbor), cluster.max(neighbor));
+            for &(first, second) in &self.borders[&key] {
+                let (from, to) = if cluster < neighbor {
+                    (first, second)
+                } else {
+                    (second, first)
+                };
+                edges.entry(from).or_default().push(Successor {
+                    position: to,
+                    cost: self.grid.step_cost(from, to),
+                });
+            }
+        }
+
+        let bounds = self.clustering.bounds(cluster);
+        let nodes: Vec<Position> = edges.keys().copied().collect();
+        for &node in &nodes {
+            let paths = ClusterPaths::new(&self.grid, bounds, node);
+            let intra_edges = nodes
+                .iter()
+                .filter(|other| **other != node)
+                .filter_map(|other| {
+                    paths.cost(*other).map(|cost| Successor {
+                        position: *other,
+                        cost,
+                    })
+                });
+            edges.entry(node).or_default().extend(intra_edges);
+        }
+        Cluster { edges }
+    }
+}
+
+// Searches with a copy that is brought up to date first if the grid changed since the last
+// `update`, so the abstract edges always match the grid the path is refined on.
+impl Algorithm for HierarchicalMap {
+    fn start_search(
+        &self,
+        grid: &Grid,
+        start: Position,
+        goal: Position,
+    ) -> Result<Box<dyn Search>, PathError> {
+        if *grid == self.grid {
+            return Ok(Box::new(self.new_search(start, goal)));
+        }
+        let mut hierarchical_map = self.clone();
+        hierarchical_map.update(grid);
+        Ok(Box::new(hierarchical_map.new_search(start, goal)))
+    }
+}
+
+fn add_entrance(transitions: &mut Vec<(Position, Position)>, entrance: &[(Position, Position)]) {
+    if entrance.is_empty() {
+        return;
+    }
+    if entrance.len() >= MAX_ENTRANCE_WIDTH {
+        transitions.push(entrance[0]);
+        transitions.push(entrance[entrance.len() - 1]);
+    } else {
+        transitions.push(entrance[entrance.len() / 2]);
+    }
+}
+
+// The cheapest paths from one tile to the tiles of its cluster, found by a Dijkstra search that
+// never leaves the cluster. Used to connect the transitions of a cluster and to refine abstract
+// edges back into tiles.
+struct ClusterPaths {
+    bounds: Bounds,
+    g_costs: Vec<Option<i32>>,
+    came_from: Vec<Option<Position>>,
+}
+
+impl ClusterPaths {
+    fn new(grid: &Grid, bounds: Bounds, from: Position) -> Self {
+        let tiles = usize::try_from((bounds.max.0 - bounds.min.0) * (bounds.max.1 - bounds.min.1))
+            .expect("the cluster should have a size");
+        let mut paths = ClusterPaths {
+            bounds,
+            g_costs: vec![None; tiles],
+            came_from: vec![None; tiles],
+        };
+        let mut closed = vec![false; tiles];
+        let mut open = BinaryHeap::new();
+        let from_index = paths.index(from);
+        paths.g_costs[from_index] = Some(0);
+        open.push(Reverse((0, from)));
+        while let Some(Reverse((g_cost, position))) = open.pop() {
+            let index = paths.index(position);
+            if closed[index] {
+                continue;
+            }
+            closed[index] = true;
+            for successor in grid.get_successors(&position) {
+                if !bounds.contains(successor.position) {
+                    continue;
+                }
+                let new_g_cost = g_cost + successor.cost;
+                let successor_index = paths.index(successor.position);
+                if paths.g_costs[successor_index].is_none_or(|old_g_cost| new_g_cost < old_g_cost) {
+                    paths.g_costs[successor_index] = Some(new_g_cost);
+                    paths.came_from[successor_index] = Some(position);
+                    open.push(Reverse((new_g_cost, successor.position)));
+                }
+            }
+        }
+        paths
+    }
+
+    fn index(&self, position: Position) -> usize {
+        let width = self.bounds.max.0 - self.bounds.min.0;
+        usize::try_from((position.1 - self.bounds.min.1) * width + position.0 - self.bounds.min.0)
+            .expect("the position should be inside the cluster")
+    }
+
+    fn cost(&self, position: Position) -> Option<i32> {
+        if self.bounds.contains(position) {
+            self.g_costs[self.index(position)]
+        } else {
+            None
+        }
+    }
+
+    // The tiles after the starting tile up to and including `to`.
+    fn path_to(&self, to: Position) -> Vec<Position> {
+        let mut path = Vec::new();
+        let mut position = to;
+        while let Some(previous) = self.came_from[self.index(position)] {
+            path.push(position);
+            position = previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// A* over the abstract graph of a `HierarchicalMap`. The open set, closed set and current node
+/// are transition tiles, the path is refined into every tile once the goal is reached.
+#[derive(Debug)]
+pub struct HierarchicalSearch {
+    clustering: Clustering,
+    allow_diagonals: bool,
+    // Shared with the `HierarchicalMap`, rebuilding a cluster replaces it instead of changing it.
+    clusters: Vec<Arc<Cluster>>,
+    start: Position,
+    goal: Position,
+    start_edges: Vec<Successor>,
+    goal_edges: HashMap<Position, i32>,
+    open: BinaryHeap<Reverse<(i32, i32, Position)>>,
+    g_costs: HashMap<Position, i32>,
+    came_from: HashMap<Position, Position>,
+    closed: HashSet<Position>,
+    current: Option<Position>,
+    status: SearchStatus,
+    path: Vec<Position>,
+    nodes_expanded: usize,
+}
+
+impl HierarchicalSearch {
+    fn new(
+        clustering: Clustering,
+        allow_diagonals: bool,
+        clusters: Vec<Arc<Cluster>>,
+        start: Position,
+        goal: Position,
+        start_edges: Vec<Successor>,
+        goal_edges: HashMap<Position, i32>,
+    ) -> Self {
+        let mut search = HierarchicalSearch {
+            clustering,
+            allow_diagonals,
+            clusters,
+            start,
+            goal,
+            start_edges,
+            goal_edges,
+            open: BinaryHeap::new(),
+            g_costs: HashMap::new(),
+            came_from: HashMap::new(),
+            closed: HashSet::new(),
+            current: None,
+            status: SearchStatus::Searching,
+            path: Vec::new(),
+            nodes_expanded: 0,
+        };
+        search.g_costs.insert(start, 0);
+        search.push(start, 0);
+        search
+    }
+
+    // Chebyshev distance once diagonals are allowed, so the heuristic never overestimates.
+    fn heuristic(&self, position: Position) -> i32 {
+        let steps = if self.allow_diagonals {
+            (position.0 - self.goal.0)
+                .abs()
+                .max((position.1 - self.goal.1).abs())
+        } else {
+            position.distance(&self.goal)
+        };
+        steps * STRAIGHT_STEP_COST
+    }
+
+    fn push(&mut self, position: Position, g_cost: i32) {
+        let h_cost = self.heuristic(position);
+        self.open.push(Reverse((g_cost + h_cost, h_cost, position)));
+    }
+
+    fn pop(&mut self) -> Option<Position> {
+        while let Some(Reverse((_, _, position))) = self.open.pop() {
+            if !self.closed.contains(&position) {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    fn successors(&self, position: Position) -> Vec<Successor> {
+        let cluster = &self.clusters[self.clustering.cluster_of(position)];
+        let mut successors = cluster.edges.get(&position).cloned().unwrap_or_default();
+        if position == self.start {
+            successors.extend(self.start_edges.iter().copied());
+        }
+        if let Some(&cost) = self.goal_edges.get(&position) {
+            successors.push(Successor {
+                position: self.goal,
+                cost,
+            });
+        }
+        successors
+    }
+
+    // Walks the abstract path back from the goal and replaces every edge inside a cluster with
+    // the tiles of its cheapest path.
+    fn refine_path(&self, grid: &Grid) -> Vec<Position> {
+        let mut abstract_path = vec![self.goal];
+        let mut position = self.goal;
+        while let Some(previous) = self.came_from.get(&position) {
+            abstract_path.push(*previous);
+            position = *previous;
+        }
+        abstract_path.reverse();
+
+        let mut path = vec![self.start];
+        for pair in abstract_path.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let cluster = self.clustering.cluster_of(from);
+            if cluster != self.clustering.cluster_of(to) {
+                path.push(to);
+                continue;
+            }
+            let paths = ClusterPaths::new(grid, self.clustering.bounds(cluster), from);
+            path.extend(paths.path_to(to));
+        }
+        path
+    }
+}
+
+impl Search for HierarchicalSearch {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        let Some(position) = self.pop() else {
+            self.current = None;
+            self.status = SearchStatus::NotFound;
+            return self.status;
+        };
+        self.current = Some(position);
+        self.closed.insert(position);
+        self.nodes_expanded += 1;
+        if position == self.goal {
+            self.path = self.refine_path(grid);
+            self.status = SearchStatus::Found;
+            return self.status;
+        }
+
+        let g_cost = self.g_costs[&position];
+        for successor in self.successors(position) {
+            if self.closed.contains(&successor.position) {
+                continue;
+            }
+            let new_g_cost = g_cost + successor.cost;
+            if self
+                .g_costs
+                .get(&successor.position)
+                .is_none_or(|&old_g_cost| new_g_cost < old_g_cost)
+            {
+                self.g_costs.insert(successor.position, new_g_cost);
+                self.came_from.insert(successor.position, position);
+                self.push(successor.position, new_g_cost);
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        let open: HashSet<Position> = self
+            .open
+            .iter()
+            .map(|Reverse((_, _, position))| *position)
+            .filter(|position| !self.closed.contains(position))
+            .collect();
+        open.into_iter().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.closed.iter().copied().collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.path.clone()
+    }
+
+    fn cost(&self) -> Option<i32> {
+        if self.status == SearchStatus::Found {
+            self.g_costs.get(&self.goal).copied()
+        } else {
+            None
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.g_costs.get(&position).copied()
+    }
+}
+
+// References
+// 1. Near Optimal Hierarchical Path-Finding (HPA*), Adi Botea, Martin Müller and Jonathan Schaeffer
+// https://webdocs.cs.ualberta.ca/~mmueller/ps/hpastar.pdf
@@ -0,0 +1,207 @@
+use super::{DiagonalPolicy, Grid, Position, Successor, DIAGONAL_STEP_COST, STRAIGHT_STEP_COST};
+
+/// Returns the jump points reachable from `position`, skipping every neighbor that has an
+/// equally short path which doesn't pass through `position`. Only valid on uniform-cost maps.
+#[must_use]
+pub fn jump_successors(
+    grid: &Grid,
+    position: Position,
+    parent: Option<Position>,
+    goal: Position,
+    cost: i32,
+) -> Vec<Successor> {
+    pruned_neighbors(grid, position, parent)
+        .into_iter()
+        .filter_map(|neighbor| {
+            let direction = (neighbor.0 - position.0, neighbor.1 - position.1);
+            jump(grid, position, direction, goal)
+        })
+        .map(|jump_point| Successor {
+            position: jump_point,
+            cost: cost * line_cost(position, jump_point),
+        })
+        .collect()
+}
+
+/// Fills in the tiles between consecutive jump points, which always lie on a straight or
+/// diagonal line.
+#[must_use]
+pub fn interpolate_jump_points(jump_points: &[Position]) -> Vec<Position> {
+    let mut path = Vec::new();
+    for pair in jump_points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let dx = (to.0 - from.0).signum();
+        let dy = (to.1 - from.1).signum();
+        let mut position = from;
+        while position != to {
+            path.push(position);
+            position = Position(position.0 + dx, position.1 + dy);
+        }
+    }
+    if let Some(last) = jump_points.last() {
+        path.push(*last);
+    }
+    path
+}
+
+// Jumps always go in a straight or diagonal line, so every step between them costs the same.
+fn line_cost(from: Position, to: Position) -> i32 {
+    let dx = (to.0 - from.0).abs();
+    let dy = (to.1 - from.1).abs();
+    let step_cost = if dx != 0 && dy != 0 {
+        DIAGONAL_STEP_COST
+    } else {
+        STRAIGHT_STEP_COST
+    };
+    dx.max(dy) * step_cost
+}
+
+fn pruned_neighbors(grid: &Grid, position: Position, parent: Option<Position>) -> Vec<Position> {
+    let Some(parent) = parent else {
+        // The start node has no direction of travel, so nothing can be pruned.
+        return grid
+            .get_successors(&position)
+            .iter()
+            .map(|successor| successor.position)
+            .collect();
+    };
+    let Position(x, y) = position;
+    let dx = (x - parent.0).signum();
+    let dy = (y - parent.1).signum();
+    let walkable = |x, y| grid.is_walkable(x, y);
+
+    let mut neighbors = Vec::new();
+    match grid.diagonal_policy {
+        DiagonalPolicy::Never => {
+            if dx != 0 {
+                neighbors.push(Position(x, y - 1));
+                neighbors.push(Position(x, y + 1));
+                neighbors.push(Position(x + dx, y));
+            } else {
+                neighbors.push(Position(x - 1, y));
+                neighbors.push(Position(x + 1, y));
+                neighbors.push(Position(x, y + dy));
+            }
+        }
+        // The forced neighbors are the same whether or not a diagonal step may squeeze between
+        // two blocked tiles, `can_step` below drops the ones the policy doesn't allow.
+        DiagonalPolicy::Always | DiagonalPolicy::NoCornerCutting => {
+            if dx != 0 && dy != 0 {
+                neighbors.push(Position(x, y + dy));
+                neighbors.push(Position(x + dx, y));
+                neighbors.push(Position(x + dx, y + dy));
+                if !walkable(x - dx, y) {
+                    neighbors.push(Position(x - dx, y + dy));
+                }
+                if !walkable(x, y - dy) {
+                    neighbors.push(Position(x + dx, y - dy));
+                }
+            } else if dx == 0 {
+                neighbors.push(Position(x, y + dy));
+                if !walkable(x + 1, y) {
+                    neighbors.push(Position(x + 1, y + dy));
+                }
+                if !walkable(x - 1, y) {
+                    neighbors.push(Position(x - 1, y + dy));
+                }
+            } else {
+                neighbors.push(Position(x + dx, y));
+                if !walkable(x, y + 1) {
+                    neighbors.push(Position(x + dx, y + 1));
+                }
+                if !walkable(x, y - 1) {
+                    neighbors.push(Position(x + dx, y - 1));
+                }
+            }
+        }
+        // Without corner cutting nothing is ever forced around an obstacle diagonally, instead
+        // straight moves keep both sides open.
+        DiagonalPolicy::OnlyWhenNoObstacles => {
+            if dx != 0 && dy != 0 {
+                neighbors.push(Position(x, y + dy));
+                neighbors.push(Position(x + dx, y));
+                neighbors.push(Position(x + dx, y + dy));
+            } else if dx == 0 {
+                neighbors.push(Position(x, y + dy));
+                neighbors.push(Position(x + 1, y + dy));
+                neighbors.push(Position(x - 1, y + dy));
+                neighbors.push(Position(x + 1, y));
+                neighbors.push(Position(x - 1, y));
+            } else {
+                neighbors.push(Position(x + dx, y));
+                neighbors.push(Position(x + dx, y + 1));
+                neighbors.push(Position(x + dx, y - 1));
+                neighbors.push(Position(x, y + 1));
+                neighbors.push(Position(x, y - 1));
+            }
+        }
+    }
+    neighbors.retain(|neighbor| grid.can_step(position, (neighbor.0 - x, neighbor.1 - y)));
+    neighbors
+}
+
+// Walks from `from` in `direction` until it reaches the goal, a node with a forced neighbor,
+// or a step the grid doesn't allow. Diagonal moves (and vertical moves without diagonals) also
+// stop wherever one of their straight components would find a jump point.
+fn jump(grid: &Grid, from: Position, direction: (i32, i32), goal: Position) -> Option<Position> {
+    let (dx, dy) = direction;
+    let walkable = |x, y| grid.is_walkable(x, y);
+    let Position(mut x, mut y) = from;
+    loop {
+        if !grid.can_step(Position(x, y), direction) {
+            return None;
+        }
+        x += dx;
+        y += dy;
+        let position = Position(x, y);
+        if position == goal {
+            return Some(position);
+        }
+        let is_jump_point = match grid.diagonal_policy {
+            DiagonalPolicy::Never => {
+                if dx != 0 {
+                    (walkable(x, y - 1) && !walkable(x - dx, y - 1))
+                        || (walkable(x, y + 1) && !walkable(x - dx, y + 1))
+                } else {
+                    (walkable(x - 1, y) && !walkable(x - 1, y - dy))
+                        || (walkable(x + 1, y) && !walkable(x + 1, y - dy))
+                        || jump(grid, position, (1, 0), goal).is_some()
+                        || jump(grid, position, (-1, 0), goal).is_some()
+                }
+            }
+            DiagonalPolicy::Always | DiagonalPolicy::NoCornerCutting => {
+                if dx != 0 && dy != 0 {
+                    (walkable(x - dx, y + dy) && !walkable(x - dx, y))
+                        || (walkable(x + dx, y - dy) && !walkable(x, y - dy))
+                        || jump(grid, position, (dx, 0), goal).is_some()
+                        || jump(grid, position, (0, dy), goal).is_some()
+                } else if dx != 0 {
+                    (walkable(x + dx, y + 1) && !walkable(x, y + 1))
+                        || (walkable(x + dx, y - 1) && !walkable(x, y - 1))
+                } else {
+                    (walkable(x + 1, y + dy) && !walkable(x + 1, y))
+                        || (walkable(x - 1, y + dy) && !walkable(x - 1, y))
+                }
+            }
+            DiagonalPolicy::OnlyWhenNoObstacles => {
+                if dx != 0 && dy != 0 {
+                    jump(grid, position, (dx, 0), goal).is_some()
+                        || jump(grid, position, (0, dy), goal).is_some()
+                } else if dx != 0 {
+                    (walkable(x, y - 1) && !walkable(x - dx, y - 1))
+                        || (walkable(x, y + 1) && !walkable(x - dx, y + 1))
+                } else {
+                    (walkable(x - 1, y) && !walkable(x - 1, y - dy))
+                        || (walkable(x + 1, y) && !walkable(x + 1, y - dy))
+                }
+            }
+        };
+        if is_jump_point {
+            return Some(position);
+        }
+    }
+}
+
+// References
+// 1. PathFinding.js jump point finders for every diagonal movement option
+// https://github.com/qiao/PathFinding.js/tree/master/src/finders
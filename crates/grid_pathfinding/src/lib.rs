@@ -0,0 +1,49 @@
+//! Pathfinding on weighted 2D grids: A*, weighted A*, ARA*, Dijkstra, BFS, Jump Point Search,
+//! Theta*, D* Lite, HPA*, flow fields and bidirectional searches, without depending on any game
+//! engine. Greedy best-first search, DFS, IDDFS and IDA* are there to compare them with.
+//!
+//! The optional `bevy` feature adds a `PathfindingPlugin` that answers `PathRequest` events.
+
+#![warn(clippy::all, clippy::pedantic)]
+
+mod algorithm;
+mod ara_star;
+mod d_star_lite;
+mod depth_first;
+mod flow_field;
+mod grid;
+mod heuristic;
+mod hpa_star;
+mod jump_point_search;
+mod movement_profile;
+mod moving_ai;
+mod path;
+#[cfg(feature = "bevy")]
+#[allow(
+    clippy::needless_pass_by_value,
+    reason = "Bevy systems take their resources by value"
+)]
+mod plugin;
+mod search;
+mod terrain;
+mod theta_star;
+mod trace;
+
+pub use algorithm::*;
+pub use ara_star::*;
+pub use d_star_lite::*;
+pub use depth_first::*;
+pub use flow_field::*;
+pub use grid::*;
+pub use heuristic::*;
+pub use hpa_star::*;
+pub use jump_point_search::*;
+pub use movement_profile::*;
+pub use moving_ai::*;
+pub use path::*;
+#[cfg(feature = "bevy")]
+pub use plugin::*;
+pub use search::*;
+pub use terrain::*;
+pub use theta_star::*;
+pub use trace::*;
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use super::{whole_cost, DiagonalPolicy, Grid, TerrainId};
+
+/// How one kind of unit moves over a grid, so different units can search the same map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MovementProfile {
+    pub name: String,
+    /// Multiplies the cost of stepping onto a terrain, terrains without one keep their cost.
+    /// Costs are rounded and never drop below 1, so 0 makes every step onto the terrain cost 1.
+    pub cost_multipliers: HashMap<TerrainId, f64>,
+    /// Makes a terrain passable or impassable regardless of the grid.
+    pub passability: HashMap<TerrainId, bool>,
+    /// Replaces the diagonal policy of the grid, `None` keeps it.
+    pub diagonal_policy: Option<DiagonalPolicy>,
+}
+
+impl Default for MovementProfile {
+    fn default() -> Self {
+        MovementProfile {
+            name: "Walker".to_string(),
+            cost_multipliers: HashMap::new(),
+            passability: HashMap::new(),
+            diagonal_policy: None,
+        }
+    }
+}
+
+impl MovementProfile {
+    /// The walker, who follows the grid as it is, and three units that each ignore part of it.
+    #[must_use]
+    pub fn defaults() -> Vec<MovementProfile> {
+        vec![
+            MovementProfile::default(),
+            // Wades through water, but won't walk over lava.
+            MovementProfile {
+                name: "Infantry".to_string(),
+                passability: HashMap::from([(TerrainId::WATER, true), (TerrainId::LAVA, false)]),
+                ..MovementProfile::default()
+            },
+            // Gets stuck in mud and can't cross water or lava.
+            MovementProfile {
+                name: "Tank".to_string(),
+                cost_multipliers: HashMap::from([(TerrainId::MUD, 3.0), (TerrainId::GRASS, 1.5)]),
+                passability: HashMap::from([(TerrainId::WATER, false), (TerrainId::LAVA, false)]),
+                ..MovementProfile::default()
+            },
+            // Flies over everything except walls, at the same cost everywhere.
+            MovementProfile {
+                name: "Flyer".to_string(),
+                cost_multipliers: [
+                    TerrainId::GRASS,
+                    TerrainId::MUD,
+                    TerrainId::WATER,
+                    TerrainId::LAVA,
+                ]
+                .into_iter()
+                .map(|terrain| (terrain, 0.0))
+                .collect(),
+                passability: HashMap::from([(TerrainId::WATER, true), (TerrainId::LAVA, true)]),
+                diagonal_policy: Some(DiagonalPolicy::Always),
+            },
+        ]
+    }
+
+    /// The grid as a unit with this profile sees it, which every algorithm can search as is.
+    /// Costs set with `Grid::set_cost` are scaled by the multiplier of the tile's terrain.
+    #[must_use]
+    pub fn apply(&self, grid: &Grid) -> Grid {
+        let mut profiled = grid.clone();
+        for (index, terrain) in grid.terrain.iter().enumerate() {
+            if let Some(passable) = self.passability.get(terrain) {
+                profiled.blocked[index] = !passable;
+            }
+            if let Some(multiplier) = self.cost_multipliers.get(terrain) {
+                let cost = f64::from(grid.costs[index].unwrap_or(1)) * multiplier;
+                profiled.costs[index] = Some(whole_cost(cost.round()).max(1));
+            }
+        }
+        if let Some(diagonal_policy) = self.diagonal_policy {
+            profiled.diagonal_policy = diagonal_policy;
+        }
+        profiled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Position, TerrainRegistry};
+
+    fn tank() -> MovementProfile {
+        MovementProfile::defaults()
+            .into_iter()
+            .find(|profile| profile.name == "Tank")
+            .expect("the tank should be a default profile")
+    }
+
+    #[test]
+    fn profiles_keep_costs_set_on_tiles() {
+        let terrains = TerrainRegistry::default();
+        let mut grid = Grid::new(3, 1, DiagonalPolicy::Never);
+        grid.set_terrain(Position(0, 0), TerrainId::MUD, &terrains);
+        grid.set_terrain(Position(1, 0), TerrainId::MUD, &terrains);
+        grid.set_cost(Position(1, 0), 2);
+        grid.set_cost(Position(2, 0), 7);
+        let profiled = tank().apply(&grid);
+        assert_eq!(profiled.costs, vec![Some(12), Some(6), Some(7)]);
+
+        // Costs set on tiles outlast a refresh from the terrain, but not painting over them.
+        grid.refresh_costs(&terrains);
+        assert_eq!(grid.costs, vec![Some(4), Some(2), Some(7)]);
+        grid.set_terrain(Position(1, 0), TerrainId::MUD, &terrains);
+        assert_eq!(tank().apply(&grid).costs, vec![Some(12), Some(12), Some(7)]);
+    }
+}
@@ -0,0 +1,469 @@
+use std::error::Error;
+use std::f64::consts::SQRT_2;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::{
+    find_path, whole_cost, Algorithm, DiagonalPolicy, Grid, PathError, PathOptions, PathResult,
+    Position, TerrainId, TerrainRegistry, DIAGONAL_STEP_COST, STRAIGHT_STEP_COST,
+};
+
+// The scenario files give lengths with 8 decimals.
+const LENGTH_TOLERANCE: f64 = 1e-4;
+
+/// A single entry of a Moving AI `.scen` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub bucket: u32,
+    /// The `.map` file the scenario was made for.
+    pub map: String,
+    pub width: i32,
+    pub height: i32,
+    pub start: Position,
+    pub goal: Position,
+    /// The length of a shortest 8-connected path, where diagonal steps cost √2 and can't cut
+    /// corners.
+    pub optimal_length: f64,
+}
+
+impl Scenario {
+    /// The published optimal length in the step costs the searches minimize, where a straight
+    /// step costs `STRAIGHT_STEP_COST` and a diagonal one `DIAGONAL_STEP_COST`. `None` if the
+    /// length isn't made of whole straight and diagonal steps.
+    #[must_use]
+    pub fn optimal_cost(&self) -> Option<i32> {
+        // √2 is irrational, so only one number of diagonal steps leaves a whole number of
+        // straight ones.
+        let mut diagonal_steps = 0;
+        loop {
+            let straight_steps = self.optimal_length - f64::from(diagonal_steps) * SQRT_2;
+            if straight_steps < -LENGTH_TOLERANCE {
+                return None;
+            }
+            if (straight_steps - straight_steps.round()).abs() < LENGTH_TOLERANCE {
+                return Some(
+                    whole_cost(straight_steps.round()) * STRAIGHT_STEP_COST
+                        + diagonal_steps * DIAGONAL_STEP_COST,
+                );
+            }
+            diagonal_steps += 1;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScenarioResult {
+    pub scenario: Scenario,
+    pub result: Result<PathResult, PathError>,
+    pub elapsed: Duration,
+}
+
+impl ScenarioResult {
+    /// The cost of the path found.
+    #[must_use]
+    pub fn cost(&self) -> Option<i32> {
+        self.result.as_ref().ok().map(|result| result.cost)
+    }
+
+    /// Whether the path found costs as much as the published optimal path. Diagonal steps cost a
+    /// little less than √2 straight ones, so on large maps a search can find a path that costs
+    /// less while being longer, and any-angle searches find cheaper paths anyway.
+    #[must_use]
+    pub fn is_optimal(&self) -> bool {
+        self.cost()
+            .is_some_and(|cost| Some(cost) == self.scenario.optimal_cost())
+    }
+}
+
+#[derive(Debug)]
+pub enum MovingAiError {
+    Io(io::Error),
+    MissingHeader {
+        field: &'static str,
+    },
+    InvalidLine {
+        line: usize,
+    },
+    UnknownTerrain {
+        character: char,
+        line: usize,
+    },
+    RowLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    RowCount {
+        expected: usize,
+        found: usize,
+    },
+    UnsupportedVersion {
+        version: String,
+    },
+}
+
+impl Display for MovingAiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovingAiError::Io(error) => write!(f, "{error}"),
+            MovingAiError::MissingHeader { field } => write!(f, "The header has no {field}."),
+            MovingAiError::InvalidLine { line } => write!(f, "Line {line} can't be read."),
+            MovingAiError::UnknownTerrain { character, line } => {
+                write!(f, "Unknown terrain {character:?} on line {line}.")
+            }
+            MovingAiError::RowLength {
+                line,
+                expected,
+                found,
+            } => write!(f, "Line {line} has {found} tiles instead of {expected}."),
+            MovingAiError::RowCount { expected, found } => {
+                write!(f, "The map has {found} rows instead of {expected}.")
+            }
+            MovingAiError::UnsupportedVersion { version } => {
+                write!(f, "Scenario version {version} isn't supported.")
+            }
+        }
+    }
+}
+
+impl Error for MovingAiError {}
+
+impl From<io::Error> for MovingAiError {
+    fn from(error: io::Error) -> Self {
+        MovingAiError::Io(error)
+    }
+}
+
+/// Reads a Moving AI `.map` file, see `parse_moving_ai_map`.
+///
+/// # Errors
+/// Returns an error if the file can't be read or isn't a valid map.
+pub fn load_moving_ai_map(path: impl AsRef<Path>) -> Result<Grid, MovingAiError> {
+    parse_moving_ai_map(&fs::read_to_string(path)?)
+}
+
+/// Turns the contents of a Moving AI `.map` file into a grid where diagonal steps can't cut
+/// corners, like in the benchmarks. Rows are read top to bottom, so row `y` of the file becomes
+/// row `y` of the grid, matching the positions in `.scen` files.
+///
+/// `.` and `G` are floor and `@`, `O` and `T` are walls. Swamp `S` is drawn as mud but costs 1 like
+/// ground, as in the published path lengths, so Jump Point Search and Theta* can search every map.
+/// Water `W` is blocked, as it can't be entered from ground, but a `MovementProfile` can still let
+/// units wade through it.
+///
+/// # Errors
+/// Returns an error if the header is missing a field or the tiles don't match its size.
+pub fn parse_moving_ai_map(contents: &str) -> Result<Grid, MovingAiError> {
+    let mut lines = contents.lines().enumerate();
+    let (mut width, mut height) = (None, None);
+    for (index, line) in lines.by_ref() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("map") => break,
+            Some("height") => height = parse_header_field(fields.next(), index)?,
+            Some("width") => width = parse_header_field(fields.next(), index)?,
+            // `type octile` is the only type in use.
+            _ => {}
+        }
+    }
+    let width = width.ok_or(MovingAiError::MissingHeader { field: "width" })?;
+    let height = height.ok_or(MovingAiError::MissingHeader { field: "height" })?;
+    // `parse_header_field` only accepts sizes that fit.
+    let mut grid = Grid::new(
+        i32::try_from(width).unwrap_or_default(),
+        i32::try_from(height).unwrap_or_default(),
+        DiagonalPolicy::OnlyWhenNoObstacles,
+    );
+    let terrains = TerrainRegistry::default();
+    let mut rows = 0;
+    for (index, line) in lines {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if rows == height {
+            return Err(MovingAiError::RowCount {
+                expected: height,
+                found: rows + 1,
+            });
+        }
+        if line.chars().count() != width {
+            return Err(MovingAiError::RowLength {
+                line: index + 1,
+                expected: width,
+                found: line.chars().count(),
+            });
+        }
+        // Fits, as there are no more rows than `height`.
+        let y = i32::try_from(rows).unwrap_or_default();
+        for (x, character) in (0..).zip(line.chars()) {
+            let terrain = match character {
+                '.' | 'G' => TerrainId::FLOOR,
+                'S' => TerrainId::MUD,
+                '@' | 'O' | 'T' => TerrainId::WALL,
+                'W' => TerrainId::WATER,
+                _ => {
+                    return Err(MovingAiError::UnknownTerrain {
+                        character,
+                        line: index + 1,
+                    })
+                }
+            };
+            grid.set_terrain(Position(x, y), terrain, &terrains);
+            if terrain == TerrainId::MUD {
+                grid.set_cost(Position(x, y), 1);
+            }
+        }
+        rows += 1;
+    }
+    if rows != height {
+        return Err(MovingAiError::RowCount {
+            expected: height,
+            found: rows,
+        });
+    }
+    Ok(grid)
+}
+
+fn parse_header_field(field: Option<&str>, index: usize) -> Result<Option<usize>, MovingAiError> {
+    field
+        .and_then(|field| field.parse().ok())
+        // Grids are sized in `i32`.
+        .filter(|value| *value > 0 && i32::try_from(*value).is_ok())
+        .map(Some)
+        .ok_or(MovingAiError::InvalidLine { line: index + 1 })
+}
+
+/// Reads a Moving AI `.scen` file, see `parse_moving_ai_scenarios`.
+///
+/// # Errors
+/// Returns an error if the file can't be read or isn't a valid scenario file.
+pub fn load_moving_ai_scenarios(path: impl AsRef<Path>) -> Result<Vec<Scenario>, MovingAiError> {
+    parse_moving_ai_scenarios(&fs::read_to_string(path)?)
+}
+
+/// Turns the contents of a version 1 Moving AI `.scen` file into its scenarios.
+///
+/// # Errors
+/// Returns an error if the version isn't supported or a line doesn't have all nine fields.
+pub fn parse_moving_ai_scenarios(contents: &str) -> Result<Vec<Scenario>, MovingAiError> {
+    let mut scenarios = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [] => {}
+            ["version", version] => {
+                if version.parse::<f64>().ok() != Some(1.0) {
+                    return Err(MovingAiError::UnsupportedVersion {
+                        version: (*version).to_string(),
+                    });
+                }
+            }
+            [bucket, map, width, height, start_x, start_y, goal_x, goal_y, optimal_length] => {
+                let invalid_line = || MovingAiError::InvalidLine { line: index + 1 };
+                let number = |field: &str| field.parse::<i32>().map_err(|_| invalid_line());
+                scenarios.push(Scenario {
+                    bucket: bucket.parse().map_err(|_| invalid_line())?,
+                    map: (*map).to_string(),
+                    width: number(width)?,
+                    height: number(height)?,
+                    start: Position(number(start_x)?, number(start_y)?),
+                    goal: Position(number(goal_x)?, number(goal_y)?),
+                    optimal_length: optimal_length.parse().map_err(|_| invalid_line())?,
+                });
+            }
+            _ => return Err(MovingAiError::InvalidLine { line: index + 1 }),
+        }
+    }
+    Ok(scenarios)
+}
+
+/// Solves every scenario on the grid with the algorithm. Use `ScenarioResult::is_optimal` to
+/// compare the path found with the published optimal length.
+pub fn run_scenarios<A: Algorithm + ?Sized>(
+    grid: &Grid,
+    scenarios: &[Scenario],
+    algorithm: &A,
+    options: &PathOptions,
+) -> Vec<ScenarioResult> {
+    scenarios
+        .iter()
+        .map(|scenario| {
+            let started = Instant::now();
+            let result = find_path(grid, scenario.start, scenario.goal, algorithm, options);
+            let elapsed = started.elapsed();
+            ScenarioResult {
+                scenario: scenario.clone(),
+                result,
+                elapsed,
+            }
+        })
+        .collect()
+}
+
+/// The Euclidean length of a path, which also works for any-angle paths.
+#[must_use]
+pub fn path_length(path: &[Position]) -> f64 {
+    path.windows(2)
+        .map(|step| {
+            let dx = f64::from(step[1].0 - step[0].0);
+            let dy = f64::from(step[1].1 - step[0].1);
+            dx.hypot(dy)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PathfindingAlgorithm;
+
+    fn scenario(optimal_length: f64) -> Scenario {
+        Scenario {
+            bucket: 0,
+            map: "test.map".to_string(),
+            width: 3,
+            height: 2,
+            start: Position(0, 0),
+            goal: Position(2, 0),
+            optimal_length,
+        }
+    }
+
+    #[test]
+    fn map_draws_swamp_as_mud_at_the_cost_of_ground() {
+        let grid = parse_moving_ai_map("type octile\nheight 2\nwidth 3\nmap\n.GS\n@TW\n")
+            .expect("the map should be valid");
+        assert_eq!(
+            grid.terrain,
+            vec![
+                TerrainId::FLOOR,
+                TerrainId::FLOOR,
+                TerrainId::MUD,
+                TerrainId::WALL,
+                TerrainId::WALL,
+                TerrainId::WATER,
+            ]
+        );
+        assert_eq!(grid.blocked, vec![false, false, false, true, true, true]);
+        assert_eq!(grid.costs[2], Some(1));
+        assert_eq!(grid.uniform_cost(), Ok(1));
+        // The only path crosses the swamp, which Jump Point Search can search.
+        let result = find_path(
+            &grid,
+            Position(0, 0),
+            Position(2, 0),
+            &PathfindingAlgorithm::JumpPointSearch,
+            &PathOptions::default(),
+        )
+        .expect("the swamp should be passable");
+        assert_eq!(result.cost, 2 * STRAIGHT_STEP_COST);
+    }
+
+    #[test]
+    fn map_must_match_its_header() {
+        assert!(matches!(
+            parse_moving_ai_map("height 1\nmap\n..\n"),
+            Err(MovingAiError::MissingHeader { field: "width" })
+        ));
+        assert!(matches!(
+            parse_moving_ai_map("width 2\nmap\n..\n"),
+            Err(MovingAiError::MissingHeader { field: "height" })
+        ));
+        for header in ["height x\nwidth 2", "height 1\nwidth 0", "height 1\nwidth"] {
+            assert!(
+                matches!(
+                    parse_moving_ai_map(&format!("{header}\nmap\n..\n")),
+                    Err(MovingAiError::InvalidLine { .. })
+                ),
+                "{header}"
+            );
+        }
+        assert!(matches!(
+            parse_moving_ai_map("height 1\nwidth 2\nmap\n...\n"),
+            Err(MovingAiError::RowLength {
+                line: 4,
+                expected: 2,
+                found: 3
+            })
+        ));
+        assert!(matches!(
+            parse_moving_ai_map("height 2\nwidth 2\nmap\n..\n"),
+            Err(MovingAiError::RowCount {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            parse_moving_ai_map("height 1\nwidth 2\nmap\n.X\n"),
+            Err(MovingAiError::UnknownTerrain {
+                character: 'X',
+                line: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn scenarios_are_read_line_by_line() {
+        let scenarios = parse_moving_ai_scenarios(
+            "version 1\n\n0\ttest.map\t3\t2\t0\t0\t2\t0\t2.00000000\n\
+             1\ttest.map\t3\t2\t0\t0\t2\t1\t2.41421356\n",
+        )
+        .expect("the scenarios should be valid");
+        assert_eq!(scenarios.len(), 2);
+        assert_eq!(scenarios[0], scenario(2.0));
+        assert_eq!(scenarios[1].bucket, 1);
+        assert_eq!(scenarios[1].goal, Position(2, 1));
+
+        assert!(matches!(
+            parse_moving_ai_scenarios("version 2\n"),
+            Err(MovingAiError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            parse_moving_ai_scenarios("version 1\n0\ttest.map\t3\t2\t0\t0\t2\n"),
+            Err(MovingAiError::InvalidLine { line: 2 })
+        ));
+        assert!(matches!(
+            parse_moving_ai_scenarios("version 1\n0\ttest.map\t3\t2\t0\t0\t2\tx\t2\n"),
+            Err(MovingAiError::InvalidLine { line: 2 })
+        ));
+    }
+
+    #[test]
+    fn optimal_lengths_are_compared_in_step_costs() {
+        assert_eq!(scenario(2.0).optimal_cost(), Some(2 * STRAIGHT_STEP_COST));
+        assert_eq!(
+            scenario(3.0 + 2.0 * SQRT_2).optimal_cost(),
+            Some(3 * STRAIGHT_STEP_COST + 2 * DIAGONAL_STEP_COST)
+        );
+        assert_eq!(scenario(0.5).optimal_cost(), None);
+
+        let grid = Grid::new(3, 2, DiagonalPolicy::OnlyWhenNoObstacles);
+        let mut scenarios = vec![scenario(1.0 + SQRT_2), scenario(2.0)];
+        scenarios[0].goal = Position(2, 1);
+        let results = run_scenarios(
+            &grid,
+            &scenarios,
+            &PathfindingAlgorithm::AStar,
+            &PathOptions::default(),
+        );
+        assert!(results[0].is_optimal());
+        assert!(results[1].is_optimal());
+        scenarios[1].optimal_length = 1.0;
+        let results = run_scenarios(
+            &grid,
+            &scenarios[1..],
+            &PathfindingAlgorithm::AStar,
+            &PathOptions::default(),
+        );
+        assert!(!results[0].is_optimal());
+    }
+}
+
+// References
+// 1. Benchmarks for Grid-Based Pathfinding, Nathan R. Sturtevant
+// https://movingai.com/benchmarks/
+// 2. https://movingai.com/benchmarks/formats.html
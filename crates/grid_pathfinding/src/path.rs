@@ -0,0 +1,448 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use super::{
+    Algorithm, Grid, PathfindingAlgorithm, Position, Search, SearchStatus, SearchTrace,
+    TracedSearch,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathError {
+    NonUniformCosts {
+        pathfinding_algorithm: PathfindingAlgorithm,
+        min: i32,
+        max: i32,
+    },
+    OutOfBounds {
+        position: Position,
+    },
+    NoPath {
+        nodes_expanded: usize,
+    },
+    ExpansionLimitReached {
+        max_expansions: usize,
+    },
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::NonUniformCosts {
+                pathfinding_algorithm,
+                min,
+                max,
+            } => write!(
+                f,
+                "{pathfinding_algorithm} needs every open tile to have the same cost, but costs range from {min} to {max}."
+            ),
+            PathError::OutOfBounds { position } => {
+                write!(f, "{position:?} is outside of the grid.")
+            }
+            PathError::NoPath { nodes_expanded } => {
+                write!(f, "No path found after expanding {nodes_expanded} nodes.")
+            }
+            PathError::ExpansionLimitReached { max_expansions } => {
+                write!(f, "No path found within {max_expansions} node expansions.")
+            }
+        }
+    }
+}
+
+impl Error for PathError {}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PathOptions {
+    /// Gives up once this many nodes have been expanded, `None` searches until done.
+    pub max_expansions: Option<usize>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Component))]
+pub struct PathResult {
+    /// Every position from start to goal, both included. For any-angle searches like Theta*,
+    /// only the waypoints where the path turns, connected by lines of sight.
+    pub path: Vec<Position>,
+    /// In hundredths of a tile, see `STRAIGHT_STEP_COST` and `DIAGONAL_STEP_COST`.
+    pub cost: i32,
+    pub nodes_expanded: usize,
+    /// Whether `path` holds waypoints instead of every tile, see `Search::any_angle`.
+    pub any_angle: bool,
+}
+
+/// Finds a path from `start` to `goal` on the grid with the given algorithm.
+///
+/// # Errors
+/// Returns an error if the start or goal is outside of the grid, the algorithm can't search
+/// this grid, there is no path, or the search hit `options.max_expansions` first.
+pub fn find_path<A: Algorithm + ?Sized>(
+    grid: &Grid,
+    start: Position,
+    goal: Position,
+    algorithm: &A,
+    options: &PathOptions,
+) -> Result<PathResult, PathError> {
+    let mut search = start_search_in_bounds(grid, start, goal, algorithm)?;
+    run_search(search.as_mut(), grid, options)
+}
+
+/// Like `find_path`, but also returns how the search explored the grid. The trace is empty if the
+/// search couldn't be started.
+pub fn find_path_traced<A: Algorithm + ?Sized>(
+    grid: &Grid,
+    start: Position,
+    goal: Position,
+    algorithm: &A,
+    options: &PathOptions,
+) -> (Result<PathResult, PathError>, SearchTrace) {
+    let mut search = match start_search_in_bounds(grid, start, goal, algorithm) {
+        Ok(search) => TracedSearch::new(search),
+        Err(error) => return (Err(error), SearchTrace::default()),
+    };
+    let result = run_search(&mut search, grid, options);
+    (result, search.trace())
+}
+
+fn start_search_in_bounds<A: Algorithm + ?Sized>(
+    grid: &Grid,
+    start: Position,
+    goal: Position,
+    algorithm: &A,
+) -> Result<Box<dyn Search>, PathError> {
+    for position in [start, goal] {
+        if !grid.in_bounds(position.0, position.1) {
+            return Err(PathError::OutOfBounds { position });
+        }
+    }
+    algorithm.start_search(grid, start, goal)
+}
+
+fn run_search(
+    search: &mut dyn Search,
+    grid: &Grid,
+    options: &PathOptions,
+) -> Result<PathResult, PathError> {
+    loop {
+        if let Some(max_expansions) = options.max_expansions {
+            if search.status() == SearchStatus::Searching
+                && search.nodes_expanded() >= max_expansions
+            {
+                return Err(PathError::ExpansionLimitReached { max_expansions });
+            }
+        }
+        if search.step(grid) != SearchStatus::Searching {
+            return path_result(search);
+        }
+    }
+}
+
+/// The path found by a search that has finished.
+///
+/// # Errors
+/// Returns `PathError::NoPath` if the search didn't find a path.
+pub fn path_result(search: &dyn Search) -> Result<PathResult, PathError> {
+    if search.status() == SearchStatus::Found {
+        Ok(PathResult {
+            path: search.path(),
+            cost: search.cost().unwrap_or(0),
+            nodes_expanded: search.nodes_expanded(),
+            any_angle: search.any_angle(),
+        })
+    } else {
+        Err(PathError::NoPath {
+            nodes_expanded: search.nodes_expanded(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiagonalPolicy, DEFAULT_WEIGHT, DIAGONAL_STEP_COST, STRAIGHT_STEP_COST};
+
+    // Finds every cheapest path with any of these, and the fewest steps with the others.
+    const OPTIMAL: [PathfindingAlgorithm; 9] = [
+        PathfindingAlgorithm::AStar,
+        PathfindingAlgorithm::BidirectionalAStar,
+        PathfindingAlgorithm::Dijkstra,
+        PathfindingAlgorithm::JumpPointSearch,
+        PathfindingAlgorithm::DStarLite,
+        PathfindingAlgorithm::HierarchicalAStar,
+        PathfindingAlgorithm::FlowField,
+        PathfindingAlgorithm::ARAStar,
+        PathfindingAlgorithm::IDAStar,
+    ];
+    const FEWEST_STEPS: [PathfindingAlgorithm; 3] = [
+        PathfindingAlgorithm::BFS,
+        PathfindingAlgorithm::BidirectionalBFS,
+        PathfindingAlgorithm::IDDFS,
+    ];
+
+    // A 5x5 grid with a wall down the middle that leaves only the bottom row open, so the path
+    // from (0, 0) to (4, 0) has to go around it.
+    fn walled_grid(diagonal_policy: DiagonalPolicy) -> Grid {
+        let mut grid = Grid::new(5, 5, diagonal_policy);
+        for y in 0..4 {
+            let index = grid.xy_idx(2, y);
+            grid.blocked[index] = true;
+        }
+        grid
+    }
+
+    fn solve(grid: &Grid, pathfinding_algorithm: PathfindingAlgorithm) -> PathResult {
+        find_path(
+            grid,
+            Position(0, 0),
+            Position(4, 0),
+            &pathfinding_algorithm,
+            &PathOptions::default(),
+        )
+        .unwrap_or_else(|error| panic!("{pathfinding_algorithm}: {error}"))
+    }
+
+    // Checks that the path is made of allowed steps from start to goal and costs what the search
+    // says it does.
+    fn assert_valid_path(grid: &Grid, result: &PathResult, name: PathfindingAlgorithm) {
+        assert!(!result.any_angle, "{name}");
+        assert_eq!(result.path.first(), Some(&Position(0, 0)), "{name}");
+        assert_eq!(result.path.last(), Some(&Position(4, 0)), "{name}");
+        let mut cost = 0;
+        for step in result.path.windows(2) {
+            let direction = (step[1].0 - step[0].0, step[1].1 - step[0].1);
+            assert!(grid.can_step(step[0], direction), "{name}: {step:?}");
+            cost += grid.step_cost(step[0], step[1]);
+        }
+        assert_eq!(result.cost, cost, "{name}");
+    }
+
+    #[test]
+    fn searches_without_diagonals_find_the_cheapest_path() {
+        let grid = walled_grid(DiagonalPolicy::Never);
+        for pathfinding_algorithm in OPTIMAL.into_iter().chain(FEWEST_STEPS) {
+            let result = solve(&grid, pathfinding_algorithm);
+            assert_valid_path(&grid, &result, pathfinding_algorithm);
+            assert_eq!(
+                result.cost,
+                12 * STRAIGHT_STEP_COST,
+                "{pathfinding_algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn searches_with_diagonals_find_the_cheapest_path() {
+        let grid = walled_grid(DiagonalPolicy::Always);
+        for pathfinding_algorithm in FEWEST_STEPS {
+            let result = solve(&grid, pathfinding_algorithm);
+            assert_valid_path(&grid, &result, pathfinding_algorithm);
+            assert_eq!(result.path.len(), 9, "{pathfinding_algorithm}");
+        }
+        for pathfinding_algorithm in OPTIMAL {
+            let result = solve(&grid, pathfinding_algorithm);
+            assert_valid_path(&grid, &result, pathfinding_algorithm);
+            assert_eq!(
+                result.cost,
+                4 * STRAIGHT_STEP_COST + 4 * DIAGONAL_STEP_COST,
+                "{pathfinding_algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn diagonal_policy_changes_the_cheapest_path() {
+        for (diagonal_policy, cost) in [
+            (DiagonalPolicy::Never, 12 * STRAIGHT_STEP_COST),
+            (
+                DiagonalPolicy::Always,
+                4 * STRAIGHT_STEP_COST + 4 * DIAGONAL_STEP_COST,
+            ),
+            (
+                DiagonalPolicy::NoCornerCutting,
+                4 * STRAIGHT_STEP_COST + 4 * DIAGONAL_STEP_COST,
+            ),
+            // Can't step diagonally past the end of the wall.
+            (
+                DiagonalPolicy::OnlyWhenNoObstacles,
+                8 * STRAIGHT_STEP_COST + 2 * DIAGONAL_STEP_COST,
+            ),
+        ] {
+            let grid = walled_grid(diagonal_policy);
+            let result = solve(&grid, PathfindingAlgorithm::Dijkstra);
+            assert_valid_path(&grid, &result, PathfindingAlgorithm::Dijkstra);
+            assert_eq!(result.cost, cost, "{diagonal_policy}");
+        }
+    }
+
+    #[test]
+    fn weighted_a_star_stays_within_its_bound() {
+        let grid = walled_grid(DiagonalPolicy::NoCornerCutting);
+        let cheapest = solve(&grid, PathfindingAlgorithm::Dijkstra).cost;
+        let result = solve(&grid, PathfindingAlgorithm::WeightedAStar);
+        assert_valid_path(&grid, &result, PathfindingAlgorithm::WeightedAStar);
+        assert!(result.cost >= cheapest);
+        assert!(f64::from(result.cost) <= f64::from(cheapest) * DEFAULT_WEIGHT);
+    }
+
+    #[test]
+    fn teaching_searches_find_a_valid_path() {
+        let grid = walled_grid(DiagonalPolicy::NoCornerCutting);
+        let cheapest = solve(&grid, PathfindingAlgorithm::Dijkstra).cost;
+        for pathfinding_algorithm in [
+            PathfindingAlgorithm::GreedyBestFirst,
+            PathfindingAlgorithm::DFS,
+        ] {
+            let result = solve(&grid, pathfinding_algorithm);
+            assert_valid_path(&grid, &result, pathfinding_algorithm);
+            assert!(result.cost >= cheapest, "{pathfinding_algorithm}");
+        }
+    }
+
+    #[test]
+    fn any_angle_searches_are_no_longer_than_grid_paths() {
+        let grid = walled_grid(DiagonalPolicy::NoCornerCutting);
+        let cheapest = solve(&grid, PathfindingAlgorithm::Dijkstra).cost;
+        for pathfinding_algorithm in [
+            PathfindingAlgorithm::ThetaStar,
+            PathfindingAlgorithm::LazyThetaStar,
+        ] {
+            let result = solve(&grid, pathfinding_algorithm);
+            assert!(result.any_angle, "{pathfinding_algorithm}");
+            assert_eq!(result.path.first(), Some(&Position(0, 0)));
+            assert_eq!(result.path.last(), Some(&Position(4, 0)));
+            for waypoints in result.path.windows(2) {
+                assert!(
+                    grid.line_of_sight(waypoints[0], waypoints[1]),
+                    "{pathfinding_algorithm}"
+                );
+            }
+            assert!(result.cost <= cheapest, "{pathfinding_algorithm}");
+        }
+    }
+
+    #[test]
+    fn searches_avoid_expensive_tiles() {
+        // Opens the wall at the top, but stepping through the gap costs more than going around.
+        let mut grid = walled_grid(DiagonalPolicy::Never);
+        let index = grid.xy_idx(2, 0);
+        grid.blocked[index] = false;
+        grid.costs[index] = Some(10);
+        // Jump Point Search only searches grids where every open tile costs the same.
+        for pathfinding_algorithm in OPTIMAL {
+            if pathfinding_algorithm == PathfindingAlgorithm::JumpPointSearch {
+                continue;
+            }
+            let result = solve(&grid, pathfinding_algorithm);
+            assert_valid_path(&grid, &result, pathfinding_algorithm);
+            assert_eq!(
+                result.cost,
+                12 * STRAIGHT_STEP_COST,
+                "{pathfinding_algorithm}"
+            );
+        }
+        // The fewest steps go through the gap, and are reported with what they cost.
+        for pathfinding_algorithm in FEWEST_STEPS {
+            let result = solve(&grid, pathfinding_algorithm);
+            assert_valid_path(&grid, &result, pathfinding_algorithm);
+            assert_eq!(result.path.len(), 5, "{pathfinding_algorithm}");
+            assert_eq!(
+                result.cost,
+                13 * STRAIGHT_STEP_COST,
+                "{pathfinding_algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn jump_point_search_needs_uniform_costs() {
+        let mut grid = walled_grid(DiagonalPolicy::Never);
+        let index = grid.xy_idx(2, 0);
+        grid.blocked[index] = false;
+        grid.costs[index] = Some(10);
+        let result = find_path(
+            &grid,
+            Position(0, 0),
+            Position(4, 0),
+            &PathfindingAlgorithm::JumpPointSearch,
+            &PathOptions::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(PathError::NonUniformCosts {
+                min: 1,
+                max: 10,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn searches_stop_at_the_expansion_limit() {
+        let grid = walled_grid(DiagonalPolicy::Never);
+        let options = PathOptions {
+            max_expansions: Some(3),
+        };
+        let result = find_path(
+            &grid,
+            Position(0, 0),
+            Position(4, 0),
+            &PathfindingAlgorithm::Dijkstra,
+            &options,
+        );
+        assert_eq!(
+            result,
+            Err(PathError::ExpansionLimitReached { max_expansions: 3 })
+        );
+    }
+
+    #[test]
+    fn blocked_goal_has_no_path() {
+        let mut grid = walled_grid(DiagonalPolicy::Always);
+        let index = grid.xy_idx(4, 0);
+        grid.blocked[index] = true;
+        for pathfinding_algorithm in PathfindingAlgorithm::ALL {
+            let result = find_path(
+                &grid,
+                Position(0, 0),
+                Position(4, 0),
+                &pathfinding_algorithm,
+                &PathOptions::default(),
+            );
+            assert!(
+                matches!(result, Err(PathError::NoPath { .. })),
+                "{pathfinding_algorithm}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn closed_wall_has_no_path() {
+        let mut grid = walled_grid(DiagonalPolicy::Never);
+        let index = grid.xy_idx(2, 4);
+        grid.blocked[index] = true;
+        for pathfinding_algorithm in PathfindingAlgorithm::ALL {
+            let result = find_path(
+                &grid,
+                Position(0, 0),
+                Position(4, 0),
+                &pathfinding_algorithm,
+                &PathOptions::default(),
+            );
+            assert!(
+                matches!(result, Err(PathError::NoPath { .. })),
+                "{pathfinding_algorithm}: {result:?}"
+            );
+        }
+        let result = find_path(
+            &grid,
+            Position(0, 0),
+            Position(5, 0),
+            &PathfindingAlgorithm::AStar,
+            &PathOptions::default(),
+        );
+        assert_eq!(
+            result,
+            Err(PathError::OutOfBounds {
+                position: Position(5, 0)
+            })
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use bevy::prelude::*;
+use std::cmp::Reverse;
+
+use super::{
+    path_result, Algorithm, DiagonalPolicy, Grid, Heuristic, HeuristicAlgorithm, HierarchicalMap,
+    MovementProfile, PathError, PathOptions, PathResult, PathfindingAlgorithm, Position, Search,
+    SearchStatus, CLUSTER_SIZE, DEFAULT_WEIGHT,
+};
+
+/// Adds a `Grid` to the app and answers `PathRequest` events with a `PathResult` component on
+/// the requesting entity and a `PathSolvedEvent`. Searches are spread over as many frames as
+/// the `PathfindingBudget` needs, so no frame expands more nodes than it allows.
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(PathfindingSettings::default)
+            .clone();
+        if !app.world.contains_resource::<Grid>() {
+            app.insert_resource(Grid::new(
+                settings.width,
+                settings.height,
+                settings.diagonal_policy,
+            ));
+        }
+        let grid = settings
+            .movement_profile
+            .apply(app.world.resource::<Grid>());
+        let hierarchical_map = HierarchicalMap::new(&grid, CLUSTER_SIZE);
+        app.insert_resource(hierarchical_map)
+            .insert_resource(ProfiledGrid { grid })
+            .init_resource::<PathfindingBudget>()
+            .init_resource::<PathQueue>()
+            .add_event::<MapUpdatedEvent>()
+            .add_event::<PathRequest>()
+            .add_event::<PathSolvedEvent>()
+            .add_system_to_stage(CoreStage::First, reset_pathfinding_budget_system)
+            .add_system(
+                update_profiled_grid_system
+                    .before(update_hierarchical_map_system)
+                    .before(queue_path_requests_system)
+                    .before(run_path_queue_system),
+            )
+            .add_system(update_hierarchical_map_system)
+            .add_system(queue_path_requests_system.before(run_path_queue_system))
+            .add_system(run_path_queue_system);
+    }
+}
+
+// === Resources ===
+#[derive(Clone, Debug)]
+pub struct PathfindingSettings {
+    /// Size of the grid created when the plugin is added, unless the app already has a `Grid`.
+    pub width: i32,
+    pub height: i32,
+    pub diagonal_policy: DiagonalPolicy,
+    /// The algorithm used to answer `PathRequest` events.
+    pub pathfinding_algorithm: PathfindingAlgorithm,
+    /// The heuristic of A*, bidirectional A*, Jump Point Search, weighted A*, ARA*, greedy
+    /// best-first search and IDA*, `None` picks one with `Heuristic::for_grid`.
+    pub heuristic: Option<Heuristic>,
+    /// The epsilon of weighted A* and the first path of ARA*.
+    pub weight: f64,
+    /// Requests are searched on the grid as this profile sees it, and the HPA* graph is kept for
+    /// that grid.
+    pub movement_profile: MovementProfile,
+    pub options: PathOptions,
+}
+
+impl Default for PathfindingSettings {
+    fn default() -> Self {
+        PathfindingSettings {
+            width: 64,
+            height: 64,
+            diagonal_policy: DiagonalPolicy::Never,
+            pathfinding_algorithm: PathfindingAlgorithm::AStar,
+            heuristic: None,
+            weight: DEFAULT_WEIGHT,
+            movement_profile: MovementProfile::default(),
+            options: PathOptions::default(),
+        }
+    }
+}
+
+/// The `Grid` as the `movement_profile` of the `PathfindingSettings` sees it, which is what the
+/// plugin searches. It is only profiled again after the grid or the settings changed.
+#[derive(Clone, Debug)]
+pub struct ProfiledGrid {
+    grid: Grid,
+}
+
+impl ProfiledGrid {
+    #[must_use]
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+}
+
+/// Caps the node expansions of all searches together in a single frame, which keeps frame times
+/// steady even without threads, e.g. in WASM builds.
+#[derive(Clone, Copy, Debug)]
+pub struct PathfindingBudget {
+    pub max_expansions_per_frame: usize,
+    remaining: usize,
+}
+
+impl PathfindingBudget {
+    #[must_use]
+    pub fn new(max_expansions_per_frame: usize) -> Self {
+        PathfindingBudget {
+            max_expansions_per_frame,
+            remaining: max_expansions_per_frame,
+        }
+    }
+
+    /// The node expansions left in this frame.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Steps the search until it finishes or the budget of this frame runs out, and returns its
+    /// result once it finished. Every step is charged the nodes it expanded, so a step that
+    /// expands many at once, like a jump of Jump Point Search or an iteration of IDA*, can
+    /// overshoot what was left of the budget, but then ends the frame. Steps that expand nothing,
+    /// like skipping a stale node, are charged as one.
+    pub fn step(
+        &mut self,
+        search: &mut dyn Search,
+        grid: &Grid,
+        options: &PathOptions,
+    ) -> Option<Result<PathResult, PathError>> {
+        while self.remaining > 0 {
+            if let Some(max_expansions) = options.max_expansions {
+                if search.nodes_expanded() >= max_expansions {
+                    return Some(Err(PathError::ExpansionLimitReached { max_expansions }));
+                }
+            }
+            let nodes_expanded = search.nodes_expanded();
+            let status = search.step(grid);
+            let expanded = search.nodes_expanded().saturating_sub(nodes_expanded);
+            self.remaining = self.remaining.saturating_sub(expanded.max(1));
+            if status != SearchStatus::Searching {
+                return Some(path_result(search));
+            }
+        }
+        None
+    }
+}
+
+impl Default for PathfindingBudget {
+    fn default() -> Self {
+        PathfindingBudget::new(10_000)
+    }
+}
+
+/// The requests that are still being searched, highest priority first and otherwise in the
+/// order they were sent.
+#[derive(Debug, Default)]
+pub struct PathQueue {
+    requests: Vec<QueuedRequest>,
+    sent: u64,
+}
+
+impl PathQueue {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct QueuedRequest {
+    path_request: PathRequest,
+    order: u64,
+    // Requests that can't be searched are answered with the error in the next step.
+    search: Result<Box<dyn Search>, PathError>,
+    frames: u32,
+}
+
+// === Events ===
+/// Send this after editing the `Grid` resource.
+pub struct MapUpdatedEvent {}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PathRequest {
+    pub entity: Entity,
+    pub start: Position,
+    pub goal: Position,
+    /// Requests with a higher priority are searched first.
+    pub priority: i32,
+}
+
+pub struct PathSolvedEvent {
+    pub entity: Entity,
+    pub result: Result<PathResult, PathError>,
+    /// How many frames it took to answer the request, counting the frame it was sent in.
+    pub frames: u32,
+}
+
+// === Systems ===
+pub fn update_profiled_grid_system(
+    mut profiled_grid: ResMut<ProfiledGrid>,
+    settings: Res<PathfindingSettings>,
+    grid: Res<Grid>,
+) {
+    if grid.is_changed() || settings.is_changed() {
+        profiled_grid.grid = settings.movement_profile.apply(&grid);
+    }
+}
+
+// Keeps the HPA* clusters in step with the grid as the profile sees it, only rebuilding the
+// clusters that were edited.
+pub fn update_hierarchical_map_system(
+    mut map_updated_event_reader: EventReader<MapUpdatedEvent>,
+    mut hierarchical_map: ResMut<HierarchicalMap>,
+    profiled_grid: Res<ProfiledGrid>,
+) {
+    if map_updated_event_reader.iter().last().is_none() && !profiled_grid.is_changed() {
+        return;
+    }
+    let rebuilt_clusters = hierarchical_map.update(&profiled_grid.grid);
+    if rebuilt_clusters > 0 {
+        debug!(
+            "Rebuilt {} of {} HPA* clusters.",
+            rebuilt_clusters,
+            hierarchical_map.cluster_count()
+        );
+    }
+}
+
+pub fn reset_pathfinding_budget_system(mut pathfinding_budget: ResMut<PathfindingBudget>) {
+    pathfinding_budget.remaining = pathfinding_budget.max_expansions_per_frame;
+}
+
+// Starts a search for every new request and queues it by priority.
+pub fn queue_path_requests_system(
+    mut path_request_event_reader: EventReader<PathRequest>,
+    mut path_queue: ResMut<PathQueue>,
+    settings: Res<PathfindingSettings>,
+    mut hierarchical_map: ResMut<HierarchicalMap>,
+    profiled_grid: Res<ProfiledGrid>,
+) {
+    if path_request_event_reader.is_empty() {
+        return;
+    }
+    for path_request in path_request_event_reader.iter() {
+        let search = start_search(
+            path_request,
+            &settings,
+            &mut hierarchical_map,
+            &profiled_grid.grid,
+        );
+        let order = path_queue.sent;
+        path_queue.sent += 1;
+        path_queue.requests.push(QueuedRequest {
+            path_request: *path_request,
+            order,
+            search,
+            frames: 0,
+        });
+    }
+    path_queue
+        .requests
+        .sort_by_key(|queued| (Reverse(queued.path_request.priority), queued.order));
+}
+
+// Steps the queued searches in order until the budget of this frame runs out. The `PathResult`
+// component is removed from the entity when no path was found.
+pub fn run_path_queue_system(
+    mut commands: Commands,
+    mut path_solved_event_writer: EventWriter<PathSolvedEvent>,
+    mut path_queue: ResMut<PathQueue>,
+    mut pathfinding_budget: ResMut<PathfindingBudget>,
+    settings: Res<PathfindingSettings>,
+    mut hierarchical_map: ResMut<HierarchicalMap>,
+    profiled_grid: Res<ProfiledGrid>,
+) {
+    if path_queue.is_empty() {
+        return;
+    }
+    let requests = &mut path_queue.requests;
+    let grid = &profiled_grid.grid;
+    // Searches that were started on a grid or profile that has changed since start over.
+    if profiled_grid.is_changed() || settings.is_changed() {
+        for queued in requests.iter_mut() {
+            queued.search =
+                start_search(&queued.path_request, &settings, &mut hierarchical_map, grid);
+        }
+    }
+    requests.retain_mut(|queued| {
+        queued.frames += 1;
+        let result = match &mut queued.search {
+            Ok(search) => pathfinding_budget.step(search.as_mut(), grid, &settings.options),
+            Err(error) => Some(Err(*error)),
+        };
+        let Some(result) = result else {
+            return true;
+        };
+        send_path_result(
+            &mut commands,
+            &mut path_solved_event_writer,
+            queued.path_request.entity,
+            result,
+            queued.frames,
+        );
+        false
+    });
+}
+
+fn start_search(
+    path_request: &PathRequest,
+    settings: &PathfindingSettings,
+    hierarchical_map: &mut HierarchicalMap,
+    grid: &Grid,
+) -> Result<Box<dyn Search>, PathError> {
+    for position in [path_request.start, path_request.goal] {
+        if !grid.in_bounds(position.0, position.1) {
+            return Err(PathError::OutOfBounds { position });
+        }
+    }
+    let algorithm = HeuristicAlgorithm {
+        pathfinding_algorithm: settings.pathfinding_algorithm,
+        heuristic: settings
+            .heuristic
+            .unwrap_or_else(|| Heuristic::for_grid(grid)),
+        weight: settings.weight,
+    };
+    select_algorithm(&algorithm, grid, hierarchical_map).start_search(
+        grid,
+        path_request.start,
+        path_request.goal,
+    )
+}
+
+fn send_path_result(
+    commands: &mut Commands,
+    path_solved_event_writer: &mut EventWriter<PathSolvedEvent>,
+    entity: Entity,
+    result: Result<PathResult, PathError>,
+    frames: u32,
+) {
+    let path_result = result.clone().ok();
+    // The entity may have been despawned since it sent the request.
+    commands.add(move |world: &mut World| {
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            match path_result {
+                Some(path_result) => {
+                    entity.insert(path_result);
+                }
+                None => {
+                    entity.remove::<PathResult>();
+                }
+            }
+        }
+    });
+    path_solved_event_writer.send(PathSolvedEvent {
+        entity,
+        result,
+        frames,
+    });
+}
+
+/// HPA* searches the abstract graph that is kept up to date as the grid is edited, instead of
+/// building a new one for every search.
+pub fn select_algorithm<'a>(
+    algorithm: &'a HeuristicAlgorithm,
+    grid: &Grid,
+    hierarchical_map: &'a mut HierarchicalMap,
+) -> &'a dyn Algorithm {
+    if algorithm.pathfinding_algorithm == PathfindingAlgorithm::HierarchicalAStar {
+        hierarchical_map.update(grid);
+        hierarchical_map
+    } else {
+        algorithm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expands three nodes in every step, like a jump that passes over several tiles, and finds
+    // the goal after six steps.
+    #[derive(Debug, Default)]
+    struct JumpingSearch {
+        steps: usize,
+    }
+
+    impl Search for JumpingSearch {
+        fn step(&mut self, _grid: &Grid) -> SearchStatus {
+            self.steps += 1;
+            self.status()
+        }
+
+        fn status(&self) -> SearchStatus {
+            if self.steps < 6 {
+                SearchStatus::Searching
+            } else {
+                SearchStatus::Found
+            }
+        }
+
+        fn current(&self) -> Option<Position> {
+            None
+        }
+
+        fn open_set(&self) -> Vec<Position> {
+            Vec::new()
+        }
+
+        fn closed_set(&self) -> Vec<Position> {
+            Vec::new()
+        }
+
+        fn path(&self) -> Vec<Position> {
+            vec![Position(0, 0)]
+        }
+
+        fn cost(&self) -> Option<i32> {
+            (self.status() == SearchStatus::Found).then_some(0)
+        }
+
+        fn nodes_expanded(&self) -> usize {
+            self.steps * 3
+        }
+    }
+
+    #[test]
+    fn budget_is_charged_the_nodes_each_step_expands() {
+        let grid = Grid::new(1, 1, DiagonalPolicy::Never);
+        let mut search = JumpingSearch::default();
+        let mut pathfinding_budget = PathfindingBudget::new(10);
+        let result = pathfinding_budget.step(&mut search, &grid, &PathOptions::default());
+        assert!(result.is_none());
+        assert_eq!(search.steps, 4);
+        assert_eq!(pathfinding_budget.remaining(), 0);
+
+        pathfinding_budget.remaining = pathfinding_budget.max_expansions_per_frame;
+        let result = pathfinding_budget.step(&mut search, &grid, &PathOptions::default());
+        assert!(matches!(result, Some(Ok(_))));
+        assert_eq!(pathfinding_budget.remaining(), 4);
+    }
+}
@@ -0,0 +1,722 @@
+use std::any::Any;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+
+use super::{
+    interpolate_jump_points, jump_successors, whole_cost, Algorithm, AraStar, DStarLite,
+    DepthFirstSearch, FlowField, Grid, Heuristic, HierarchicalMap, PathError, PathfindingAlgorithm,
+    Position, ThetaStarSearch, CLUSTER_SIZE, DEFAULT_WEIGHT, STRAIGHT_STEP_COST,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SearchStatus {
+    Searching,
+    Found,
+    NotFound,
+}
+
+/// A search that can be paused after any node expansion and resumed later. A finished search can
+/// be turned into a `Box<dyn Any>` to get back the type that was searched with.
+pub trait Search: Any + Debug + Send + Sync {
+    /// Expands a single node and returns the status of the search afterwards.
+    fn step(&mut self, grid: &Grid) -> SearchStatus;
+
+    fn status(&self) -> SearchStatus;
+
+    /// The node that was expanded by the last call to `step`.
+    fn current(&self) -> Option<Position>;
+
+    fn open_set(&self) -> Vec<Position>;
+
+    fn closed_set(&self) -> Vec<Position>;
+
+    /// The path from start to goal, empty until the search has found one. Anytime searches like
+    /// ARA* return their best path so far while they are still improving it. Any-angle searches
+    /// only return the waypoints where the path turns, see `any_angle`.
+    fn path(&self) -> Vec<Position>;
+
+    /// The cost of `path`, `None` while there is no path.
+    fn cost(&self) -> Option<i32>;
+
+    fn nodes_expanded(&self) -> usize;
+
+    /// The cost of the cheapest way to `position` the search knows of, from where it started:
+    /// the start, or the goal for searches that run backwards. `None` if it hasn't reached it.
+    fn g_cost(&self, _position: Position) -> Option<i32> {
+        None
+    }
+
+    /// The direction of the next step from `position` towards the goal, for searches that build
+    /// a flow field over the whole grid.
+    fn direction(&self, _position: Position) -> Option<(i32, i32)> {
+        None
+    }
+
+    /// How many times more than the cheapest path the path found so far may cost at most, for
+    /// searches that trade the cheapest path for speed. `None` if the path is the cheapest one.
+    fn suboptimality_bound(&self) -> Option<f64> {
+        None
+    }
+
+    /// Whether `path` is made of straight lines between waypoints that can be any number of
+    /// tiles apart, instead of single steps between neighbouring tiles.
+    fn any_angle(&self) -> bool {
+        false
+    }
+
+    /// Steps the search until it either finds a path or runs out of nodes.
+    fn run(&mut self, grid: &Grid) -> SearchStatus {
+        while self.step(grid) == SearchStatus::Searching {}
+        self.status()
+    }
+}
+
+impl Algorithm for PathfindingAlgorithm {
+    fn start_search(
+        &self,
+        grid: &Grid,
+        start: Position,
+        goal: Position,
+    ) -> Result<Box<dyn Search>, PathError> {
+        self.start_search_with_heuristic(
+            grid,
+            start,
+            goal,
+            Heuristic::for_grid(grid),
+            DEFAULT_WEIGHT,
+        )
+    }
+}
+
+impl PathfindingAlgorithm {
+    /// Like `Algorithm::start_search`, but the algorithms that follow a heuristic use the given
+    /// one, and weighted A* and ARA* count it `weight` times.
+    ///
+    /// # Errors
+    /// Returns an error if the algorithm can't search this grid.
+    pub fn start_search_with_heuristic(
+        self,
+        grid: &Grid,
+        start: Position,
+        goal: Position,
+        heuristic: Heuristic,
+        weight: f64,
+    ) -> Result<Box<dyn Search>, PathError> {
+        let pathfinding_algorithm = self;
+        // A weight below 1 would only make the search slower without finding cheaper paths.
+        let weight = weight.max(1.0);
+        let min_cost = grid.min_cost();
+        let uniform_cost = || {
+            grid.uniform_cost()
+                .map_err(|(min, max)| PathError::NonUniformCosts {
+                    pathfinding_algorithm,
+                    min,
+                    max,
+                })
+        };
+        let search: Box<dyn Search> = match pathfinding_algorithm {
+            PathfindingAlgorithm::AStar => Box::new(BestFirstSearch::new(
+                start,
+                goal,
+                Frontier::heap(),
+                Expansion::Neighbors,
+                (heuristic, min_cost),
+                Priority::Cost { weight: 1.0 },
+            )),
+            PathfindingAlgorithm::BFS => Box::new(BestFirstSearch::new(
+                start,
+                goal,
+                Frontier::queue(),
+                Expansion::Neighbors,
+                (Heuristic::Zero, min_cost),
+                Priority::Cost { weight: 1.0 },
+            )),
+            PathfindingAlgorithm::BidirectionalAStar => Box::new(BidirectionalSearch::new(
+                start,
+                goal,
+                Frontier::heap,
+                (heuristic, min_cost),
+            )),
+            PathfindingAlgorithm::BidirectionalBFS => Box::new(BidirectionalSearch::new(
+                start,
+                goal,
+                Frontier::queue,
+                (Heuristic::Zero, min_cost),
+            )),
+            PathfindingAlgorithm::Dijkstra => Box::new(BestFirstSearch::new(
+                start,
+                goal,
+                Frontier::heap(),
+                Expansion::Neighbors,
+                (Heuristic::Zero, min_cost),
+                Priority::Cost { weight: 1.0 },
+            )),
+            PathfindingAlgorithm::JumpPointSearch => Box::new(BestFirstSearch::new(
+                start,
+                goal,
+                Frontier::heap(),
+                Expansion::JumpPoints {
+                    cost: uniform_cost()?,
+                },
+                (heuristic, min_cost),
+                Priority::Cost { weight: 1.0 },
+            )),
+            PathfindingAlgorithm::ThetaStar => {
+                Box::new(ThetaStarSearch::new(start, goal, uniform_cost()?, false))
+            }
+            PathfindingAlgorithm::LazyThetaStar => {
+                Box::new(ThetaStarSearch::new(start, goal, uniform_cost()?, true))
+            }
+            PathfindingAlgorithm::DStarLite => Box::new(DStarLite::new(grid, start, goal)),
+            PathfindingAlgorithm::FlowField => Box::new(FlowField::new(grid, start, goal)),
+            // Builds the abstract graph from scratch, keep a `HierarchicalMap` up to date and
+            // search with it instead when searching the same grid repeatedly.
+            PathfindingAlgorithm::HierarchicalAStar => {
+                Box::new(HierarchicalMap::new(grid, CLUSTER_SIZE).new_search(start, goal))
+            }
+            PathfindingAlgorithm::WeightedAStar => Box::new(BestFirstSearch::new(
+                start,
+                goal,
+                Frontier::heap(),
+                Expansion::Neighbors,
+                (heuristic, min_cost),
+                Priority::Cost { weight },
+            )),
+            PathfindingAlgorithm::ARAStar => {
+                Box::new(AraStar::new(start, goal, (heuristic, min_cost), weight))
+            }
+            PathfindingAlgorithm::GreedyBestFirst => Box::new(BestFirstSearch::new(
+                start,
+                goal,
+                Frontier::heap(),
+                Expansion::Neighbors,
+                (heuristic, min_cost),
+                Priority::Heuristic,
+            )),
+            PathfindingAlgorithm::DFS => Box::new(DepthFirstSearch::new(start, goal)),
+            PathfindingAlgorithm::IDDFS => {
+                Box::new(DepthFirstSearch::iterative_deepening(start, goal))
+            }
+            PathfindingAlgorithm::IDAStar => Box::new(DepthFirstSearch::ida_star(
+                start,
+                goal,
+                (heuristic, min_cost),
+            )),
+        };
+        Ok(search)
+    }
+}
+
+// BFS expands nodes in the order they were discovered and ignores costs,
+// Dijkstra, A* and greedy best-first search expand the most promising node first.
+#[derive(Debug)]
+enum Frontier {
+    Queue(VecDeque<Position>),
+    Heap(BinaryHeap<Reverse<(i32, i32, Position)>>),
+}
+
+impl Frontier {
+    fn queue() -> Frontier {
+        Frontier::Queue(VecDeque::new())
+    }
+
+    fn heap() -> Frontier {
+        Frontier::Heap(BinaryHeap::new())
+    }
+}
+
+// Jump Point Search only expands the jump points of a uniform-cost grid instead of every neighbor.
+#[derive(Debug)]
+enum Expansion {
+    Neighbors,
+    JumpPoints { cost: i32 },
+}
+
+// A* orders the frontier by g + h, weighted A* counts the heuristic `weight` times, which finds a
+// path sooner but not necessarily the cheapest one, and greedy best-first search only looks at the
+// heuristic, ignoring the cost so far.
+#[derive(Clone, Copy, Debug)]
+enum Priority {
+    Cost { weight: f64 },
+    Heuristic,
+}
+
+#[derive(Debug)]
+pub struct BestFirstSearch {
+    goal: Position,
+    frontier: Frontier,
+    expansion: Expansion,
+    // The heuristic and the cost of the cheapest open tile it is scaled by.
+    heuristic: (Heuristic, i32),
+    priority: Priority,
+    g_costs: HashMap<Position, i32>,
+    came_from: HashMap<Position, Position>,
+    closed: HashSet<Position>,
+    current: Option<Position>,
+    status: SearchStatus,
+    path: Vec<Position>,
+    nodes_expanded: usize,
+}
+
+impl BestFirstSearch {
+    fn new(
+        start: Position,
+        goal: Position,
+        frontier: Frontier,
+        expansion: Expansion,
+        heuristic: (Heuristic, i32),
+        priority: Priority,
+    ) -> Self {
+        let mut search = BestFirstSearch {
+            goal,
+            frontier,
+            expansion,
+            heuristic,
+            priority,
+            g_costs: HashMap::new(),
+            came_from: HashMap::new(),
+            closed: HashSet::new(),
+            current: None,
+            status: SearchStatus::Searching,
+            path: Vec::new(),
+            nodes_expanded: 0,
+        };
+        search.g_costs.insert(start, 0);
+        search.push(start, 0);
+        search
+    }
+
+    fn heuristic(&self, position: Position) -> i32 {
+        let (heuristic, min_cost) = self.heuristic;
+        heuristic.estimate(position, self.goal, min_cost)
+    }
+
+    fn push(&mut self, position: Position, g_cost: i32) {
+        let h_cost = self.heuristic(position);
+        let priority = match self.priority {
+            Priority::Cost { weight } => g_cost + whole_cost(f64::from(h_cost) * weight),
+            Priority::Heuristic => h_cost,
+        };
+        match &mut self.frontier {
+            Frontier::Queue(queue) => queue.push_back(position),
+            Frontier::Heap(heap) => heap.push(Reverse((priority, h_cost, position))),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Position> {
+        match &mut self.frontier {
+            Frontier::Queue(queue) => queue.pop_front(),
+            // The heap may hold stale entries for nodes that were reached again more cheaply.
+            Frontier::Heap(heap) => {
+                while let Some(Reverse((_, _, position))) = heap.pop() {
+                    if !self.closed.contains(&position) {
+                        return Some(position);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn reconstruct_path(&self) -> Vec<Position> {
+        let mut path = vec![self.goal];
+        let mut position = self.goal;
+        while let Some(previous) = self.came_from.get(&position) {
+            path.push(*previous);
+            position = *previous;
+        }
+        path.reverse();
+        match self.expansion {
+            Expansion::Neighbors => path,
+            Expansion::JumpPoints { .. } => interpolate_jump_points(&path),
+        }
+    }
+}
+
+impl Search for BestFirstSearch {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        let Some(position) = self.pop() else {
+            self.current = None;
+            self.status = SearchStatus::NotFound;
+            return self.status;
+        };
+        self.current = Some(position);
+        self.closed.insert(position);
+        self.nodes_expanded += 1;
+        if position == self.goal {
+            self.path = self.reconstruct_path();
+            self.status = SearchStatus::Found;
+            return self.status;
+        }
+
+        let g_cost = self.g_costs[&position];
+        let is_queue = matches!(self.frontier, Frontier::Queue(_));
+        let successors = match self.expansion {
+            Expansion::Neighbors => grid.get_successors(&position),
+            Expansion::JumpPoints { cost } => {
+                let parent = self.came_from.get(&position).copied();
+                jump_successors(grid, position, parent, self.goal, cost)
+            }
+        };
+        for successor in successors {
+            if self.closed.contains(&successor.position) {
+                continue;
+            }
+            // BFS never revisits a discovered node, so its g costs are the costs of the paths it
+            // happened to find first, which only have the fewest steps.
+            let new_g_cost = g_cost + successor.cost;
+            if let Some(&old_g_cost) = self.g_costs.get(&successor.position) {
+                if is_queue || old_g_cost <= new_g_cost {
+                    continue;
+                }
+            }
+            self.g_costs.insert(successor.position, new_g_cost);
+            self.came_from.insert(successor.position, position);
+            self.push(successor.position, new_g_cost);
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        match &self.frontier {
+            Frontier::Queue(queue) => queue.iter().copied().collect(),
+            Frontier::Heap(heap) => {
+                let open: HashSet<Position> = heap
+                    .iter()
+                    .map(|Reverse((_, _, position))| *position)
+                    .filter(|position| !self.closed.contains(position))
+                    .collect();
+                open.into_iter().collect()
+            }
+        }
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.closed.iter().copied().collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.path.clone()
+    }
+
+    fn cost(&self) -> Option<i32> {
+        if self.status == SearchStatus::Found {
+            self.g_costs.get(&self.goal).copied()
+        } else {
+            None
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.g_costs.get(&position).copied()
+    }
+
+    fn suboptimality_bound(&self) -> Option<f64> {
+        match self.priority {
+            Priority::Cost { weight } => (weight > 1.0).then_some(weight),
+            Priority::Heuristic => None,
+        }
+    }
+}
+
+// One half of a bidirectional search. The backward half walks the same moves in reverse from the
+// goal, so its costs are the costs of reaching the goal from each node.
+#[derive(Debug)]
+struct SearchDirection {
+    target: Position,
+    frontier: Frontier,
+    g_costs: HashMap<Position, i32>,
+    came_from: HashMap<Position, Position>,
+    closed: HashSet<Position>,
+}
+
+impl SearchDirection {
+    fn new(
+        from: Position,
+        target: Position,
+        frontier: Frontier,
+        heuristic: (Heuristic, i32),
+    ) -> Self {
+        let mut direction = SearchDirection {
+            target,
+            frontier,
+            g_costs: HashMap::new(),
+            came_from: HashMap::new(),
+            closed: HashSet::new(),
+        };
+        direction.g_costs.insert(from, 0);
+        direction.push(from, 0, heuristic);
+        direction
+    }
+
+    fn push(&mut self, position: Position, g_cost: i32, heuristic: (Heuristic, i32)) {
+        let (heuristic, min_cost) = heuristic;
+        let h_cost = heuristic.estimate(position, self.target, min_cost);
+        match &mut self.frontier {
+            Frontier::Queue(queue) => queue.push_back(position),
+            Frontier::Heap(heap) => heap.push(Reverse((g_cost + h_cost, h_cost, position))),
+        }
+    }
+
+    // The lowest f cost left in the frontier, or `None` once it has run out.
+    fn peek(&mut self) -> Option<i32> {
+        match &mut self.frontier {
+            Frontier::Queue(queue) => queue.front().map(|position| self.g_costs[position]),
+            Frontier::Heap(heap) => {
+                while let Some(Reverse((f_cost, _, position))) = heap.peek() {
+                    if !self.closed.contains(position) {
+                        return Some(*f_cost);
+                    }
+                    heap.pop();
+                }
+                None
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<Position> {
+        self.peek()?;
+        match &mut self.frontier {
+            Frontier::Queue(queue) => queue.pop_front(),
+            Frontier::Heap(heap) => heap.pop().map(|Reverse((_, _, position))| position),
+        }
+    }
+
+    fn open_set(&self) -> impl Iterator<Item = Position> + '_ {
+        let open: Vec<Position> = match &self.frontier {
+            Frontier::Queue(queue) => queue.iter().copied().collect(),
+            Frontier::Heap(heap) => heap
+                .iter()
+                .map(|Reverse((_, _, position))| *position)
+                .collect(),
+        };
+        open.into_iter()
+            .filter(|position| !self.closed.contains(position))
+    }
+}
+
+/// Searches from the start and the goal at the same time, one node from each side in turn, until
+/// the two searches meet. Bidirectional BFS finds the fewest steps like BFS, bidirectional A* uses
+/// the tile costs and a heuristic towards the other end on each side.
+#[derive(Debug)]
+pub struct BidirectionalSearch {
+    heuristic: (Heuristic, i32),
+    forward: SearchDirection,
+    backward: SearchDirection,
+    forward_turn: bool,
+    // The cost of the cheapest path found so far and the node where its two halves meet.
+    best: Option<(i32, Position)>,
+    current: Option<Position>,
+    status: SearchStatus,
+    path: Vec<Position>,
+    // Bidirectional BFS counts steps to know when to stop, so the path is costed once it's found.
+    path_cost: Option<i32>,
+    nodes_expanded: usize,
+}
+
+impl BidirectionalSearch {
+    fn new(
+        start: Position,
+        goal: Position,
+        frontier: fn() -> Frontier,
+        heuristic: (Heuristic, i32),
+    ) -> Self {
+        BidirectionalSearch {
+            heuristic,
+            forward: SearchDirection::new(start, goal, frontier(), heuristic),
+            backward: SearchDirection::new(goal, start, frontier(), heuristic),
+            forward_turn: true,
+            best: (start == goal).then_some((0, start)),
+            current: None,
+            status: SearchStatus::Searching,
+            path: Vec::new(),
+            path_cost: None,
+            nodes_expanded: 0,
+        }
+    }
+
+    // No cheaper path can be found once the best one costs no more than a lower bound on every
+    // path still left: the sum of both frontiers without a heuristic, or either frontier with one.
+    fn is_best_path_optimal(&self, forward_min: i32, backward_min: i32) -> bool {
+        let Some((best_cost, _)) = self.best else {
+            return false;
+        };
+        if self.heuristic.0 == Heuristic::Zero {
+            best_cost <= forward_min + backward_min
+        } else {
+            best_cost <= forward_min.max(backward_min)
+        }
+    }
+
+    fn finish(&mut self, grid: &Grid) -> SearchStatus {
+        self.current = None;
+        if let Some((_, meeting_point)) = self.best {
+            self.path = self.reconstruct_path(meeting_point);
+            self.path_cost = Some(grid.path_cost(&self.path));
+            self.status = SearchStatus::Found;
+        } else {
+            self.status = SearchStatus::NotFound;
+        }
+        self.status
+    }
+
+    fn reconstruct_path(&self, meeting_point: Position) -> Vec<Position> {
+        let mut path = vec![meeting_point];
+        let mut position = meeting_point;
+        while let Some(previous) = self.forward.came_from.get(&position) {
+            path.push(*previous);
+            position = *previous;
+        }
+        path.reverse();
+        position = meeting_point;
+        while let Some(next) = self.backward.came_from.get(&position) {
+            path.push(*next);
+            position = *next;
+        }
+        path
+    }
+}
+
+impl Search for BidirectionalSearch {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        // Without a path, one side runs out of nodes once it has explored its whole region.
+        let (Some(forward_min), Some(backward_min)) = (self.forward.peek(), self.backward.peek())
+        else {
+            return self.finish(grid);
+        };
+        if self.is_best_path_optimal(forward_min, backward_min) {
+            return self.finish(grid);
+        }
+
+        let is_forward = self.forward_turn;
+        self.forward_turn = !self.forward_turn;
+        let (direction, other) = if is_forward {
+            (&mut self.forward, &self.backward)
+        } else {
+            (&mut self.backward, &self.forward)
+        };
+        let Some(position) = direction.pop() else {
+            return self.status;
+        };
+        self.current = Some(position);
+        direction.closed.insert(position);
+        self.nodes_expanded += 1;
+
+        // Nothing can step onto a blocked goal, so the backward search has nowhere to go.
+        if !is_forward && !grid.is_walkable(position.0, position.1) {
+            return self.status;
+        }
+        let g_cost = direction.g_costs[&position];
+        let is_queue = matches!(direction.frontier, Frontier::Queue(_));
+        for successor in grid.get_successors(&position) {
+            let neighbor = successor.position;
+            if direction.closed.contains(&neighbor) {
+                continue;
+            }
+            // Going backwards, the neighbor steps onto this node and pays its cost.
+            let step_cost = if is_queue {
+                STRAIGHT_STEP_COST
+            } else if is_forward {
+                successor.cost
+            } else {
+                grid.step_cost(neighbor, position)
+            };
+            let new_g_cost = g_cost + step_cost;
+            let improved = if is_queue {
+                !direction.g_costs.contains_key(&neighbor)
+            } else {
+                direction
+                    .g_costs
+                    .get(&neighbor)
+                    .is_none_or(|&old_g_cost| new_g_cost < old_g_cost)
+            };
+            if improved {
+                direction.g_costs.insert(neighbor, new_g_cost);
+                direction.came_from.insert(neighbor, position);
+                direction.push(neighbor, new_g_cost, self.heuristic);
+            }
+            if let Some(other_g_cost) = other.g_costs.get(&neighbor) {
+                let cost = direction.g_costs[&neighbor] + other_g_cost;
+                if self.best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                    self.best = Some((cost, neighbor));
+                }
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        let open: HashSet<Position> = self
+            .forward
+            .open_set()
+            .chain(self.backward.open_set())
+            .collect();
+        open.into_iter().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.forward
+            .closed
+            .union(&self.backward.closed)
+            .copied()
+            .collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.path.clone()
+    }
+
+    fn cost(&self) -> Option<i32> {
+        self.path_cost
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        // Nodes only the backward half reached have their cost to the goal instead.
+        self.forward
+            .g_costs
+            .get(&position)
+            .or_else(|| self.backward.g_costs.get(&position))
+            .copied()
+    }
+}
+
+// References
+// 1. Introduction to the A* Algorithm
+// https://www.redblobgames.com/pathfinding/a-star/introduction.html
+// 2. Online Graph Pruning for Pathfinding on Grid Maps (Jump Point Search)
+// https://users.cecs.anu.edu.au/~dharabor/data/papers/harabor-grastien-aaai11.pdf
+// 3. Bidirectional search
+// https://en.wikipedia.org/wiki/Bidirectional_search
+// 4. Weighted A*
+// http://theory.stanford.edu/~amitp/GameProgramming/Variations.html
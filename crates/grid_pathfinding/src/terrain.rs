@@ -0,0 +1,95 @@
+/// Identifies a terrain in a `TerrainRegistry`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TerrainId(pub usize);
+
+impl TerrainId {
+    pub const FLOOR: TerrainId = TerrainId(0);
+    pub const WALL: TerrainId = TerrainId(1);
+    pub const GRASS: TerrainId = TerrainId(2);
+    pub const ROAD: TerrainId = TerrainId(3);
+    pub const MUD: TerrainId = TerrainId(4);
+    pub const WATER: TerrainId = TerrainId(5);
+    pub const LAVA: TerrainId = TerrainId(6);
+}
+
+/// What a tile is made of: how much stepping onto it costs, whether it can be stepped onto at
+/// all, and which texture draws it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Terrain {
+    pub name: String,
+    pub cost: i32,
+    pub passable: bool,
+    pub texture_index: u32,
+}
+
+impl Terrain {
+    #[must_use]
+    pub fn new(name: &str, cost: i32, passable: bool, texture_index: u32) -> Terrain {
+        Terrain {
+            name: name.to_string(),
+            cost,
+            passable,
+            texture_index,
+        }
+    }
+}
+
+/// Every terrain a grid can use. The default registry has the built in terrains in the order of
+/// the `TerrainId` constants, more can be added with `register`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TerrainRegistry {
+    terrains: Vec<Terrain>,
+}
+
+impl Default for TerrainRegistry {
+    fn default() -> Self {
+        TerrainRegistry {
+            terrains: vec![
+                Terrain::new("Floor", 1, true, 1),
+                Terrain::new("Wall", 1, false, 2),
+                Terrain::new("Grass", 2, true, 8),
+                Terrain::new("Road", 1, true, 9),
+                Terrain::new("Mud", 4, true, 10),
+                Terrain::new("Water", 8, false, 11),
+                Terrain::new("Lava", 16, true, 12),
+            ],
+        }
+    }
+}
+
+impl TerrainRegistry {
+    pub fn register(&mut self, terrain: Terrain) -> TerrainId {
+        self.terrains.push(terrain);
+        TerrainId(self.terrains.len() - 1)
+    }
+
+    #[must_use]
+    pub fn get(&self, id: TerrainId) -> Option<&Terrain> {
+        self.terrains.get(id.0)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.terrains.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.terrains.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TerrainId, &Terrain)> {
+        self.terrains
+            .iter()
+            .enumerate()
+            .map(|(index, terrain)| (TerrainId(index), terrain))
+    }
+
+    /// The terrain `offset` places after `id`, wrapping around at either end.
+    #[must_use]
+    pub fn cycle(&self, id: TerrainId, offset: i32) -> TerrainId {
+        let count = i32::try_from(self.terrains.len().max(1)).unwrap_or(i32::MAX);
+        let id = i32::try_from(id.0).unwrap_or_default();
+        TerrainId(usize::try_from((id + offset).rem_euclid(count)).unwrap_or_default())
+    }
+}
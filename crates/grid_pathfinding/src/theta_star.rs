@@ -0,0 +1,214 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::{whole_cost, Grid, Position, Search, SearchStatus, STRAIGHT_STEP_COST};
+
+// Any-angle paths have real valued lengths, so costs are kept in hundredths of a tile.
+const LINE_COST_SCALE: f64 = 100.0;
+
+/// Theta* and Lazy Theta* search the grid like A*, but let a node's parent be any earlier node
+/// it can see, which turns the zig-zagging grid path into a few straight segments.
+#[derive(Debug)]
+pub struct ThetaStarSearch {
+    goal: Position,
+    lazy: bool,
+    tile_cost: i32,
+    open: BinaryHeap<Reverse<(i32, i32, Position)>>,
+    g_costs: HashMap<Position, i32>,
+    parents: HashMap<Position, Position>,
+    closed: HashSet<Position>,
+    current: Option<Position>,
+    status: SearchStatus,
+    path: Vec<Position>,
+    nodes_expanded: usize,
+}
+
+impl ThetaStarSearch {
+    /// `tile_cost` is the cost of every open tile, any-angle searches need a uniform-cost grid.
+    #[must_use]
+    pub fn new(start: Position, goal: Position, tile_cost: i32, lazy: bool) -> Self {
+        let mut search = ThetaStarSearch {
+            goal,
+            lazy,
+            tile_cost,
+            open: BinaryHeap::new(),
+            g_costs: HashMap::new(),
+            parents: HashMap::new(),
+            closed: HashSet::new(),
+            current: None,
+            status: SearchStatus::Searching,
+            path: Vec::new(),
+            nodes_expanded: 0,
+        };
+        search.g_costs.insert(start, 0);
+        search.parents.insert(start, start);
+        search.push(start, 0);
+        search
+    }
+
+    fn line_cost(&self, from: Position, to: Position) -> i32 {
+        let dx = f64::from(to.0 - from.0);
+        let dy = f64::from(to.1 - from.1);
+        whole_cost((dx.hypot(dy) * LINE_COST_SCALE).round()) * self.tile_cost
+    }
+
+    fn push(&mut self, position: Position, g_cost: i32) {
+        let h_cost = self.line_cost(position, self.goal);
+        self.open.push(Reverse((g_cost + h_cost, h_cost, position)));
+    }
+
+    fn pop(&mut self) -> Option<Position> {
+        while let Some(Reverse((_, _, position))) = self.open.pop() {
+            if !self.closed.contains(&position) {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    fn relax(&mut self, position: Position, parent: Position, g_cost: i32) {
+        if self
+            .g_costs
+            .get(&position)
+            .is_none_or(|&old_g_cost| g_cost < old_g_cost)
+        {
+            self.g_costs.insert(position, g_cost);
+            self.parents.insert(position, parent);
+            self.push(position, g_cost);
+        }
+    }
+
+    // Lazy Theta* assumes a node can see its grandparent when the node is generated and only
+    // checks once it is expanded. If it can't, the node falls back to its best closed neighbor.
+    fn set_vertex(&mut self, grid: &Grid, position: Position) {
+        let parent = self.parents[&position];
+        if grid.line_of_sight(parent, position) {
+            return;
+        }
+        let best_neighbor = grid
+            .get_successors(&position)
+            .iter()
+            .filter(|successor| self.closed.contains(&successor.position))
+            .map(|successor| {
+                let g_cost = self.g_costs[&successor.position]
+                    + self.line_cost(successor.position, position);
+                (g_cost, successor.position)
+            })
+            .min();
+        if let Some((g_cost, neighbor)) = best_neighbor {
+            self.g_costs.insert(position, g_cost);
+            self.parents.insert(position, neighbor);
+        }
+    }
+
+    fn reconstruct_path(&self) -> Vec<Position> {
+        let mut path = vec![self.goal];
+        let mut position = self.goal;
+        while let Some(&parent) = self.parents.get(&position) {
+            if parent == position {
+                break;
+            }
+            path.push(parent);
+            position = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl Search for ThetaStarSearch {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        if self.status != SearchStatus::Searching {
+            return self.status;
+        }
+        let Some(position) = self.pop() else {
+            self.current = None;
+            self.status = SearchStatus::NotFound;
+            return self.status;
+        };
+        if self.lazy {
+            self.set_vertex(grid, position);
+        }
+        self.current = Some(position);
+        self.closed.insert(position);
+        self.nodes_expanded += 1;
+        if position == self.goal {
+            self.path = self.reconstruct_path();
+            self.status = SearchStatus::Found;
+            return self.status;
+        }
+
+        let parent = self.parents[&position];
+        let g_cost = self.g_costs[&position];
+        for successor in grid.get_successors(&position) {
+            let neighbor = successor.position;
+            if self.closed.contains(&neighbor) {
+                continue;
+            }
+            if self.lazy || grid.line_of_sight(parent, neighbor) {
+                let g_cost = self.g_costs[&parent] + self.line_cost(parent, neighbor);
+                self.relax(neighbor, parent, g_cost);
+            } else {
+                let g_cost = g_cost + self.line_cost(position, neighbor);
+                self.relax(neighbor, position, g_cost);
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        let open: HashSet<Position> = self
+            .open
+            .iter()
+            .map(|Reverse((_, _, position))| *position)
+            .filter(|position| !self.closed.contains(position))
+            .collect();
+        open.into_iter().collect()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.closed.iter().copied().collect()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.path.clone()
+    }
+
+    // Reported in the step costs of the grid searches, rounded to the nearest one.
+    fn cost(&self) -> Option<i32> {
+        if self.status == SearchStatus::Found {
+            let scaled_cost = f64::from(self.g_costs[&self.goal]);
+            Some(whole_cost(
+                (scaled_cost * f64::from(STRAIGHT_STEP_COST) / LINE_COST_SCALE).round(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.g_costs.get(&position).copied()
+    }
+
+    fn any_angle(&self) -> bool {
+        true
+    }
+}
+
+// References
+// 1. Theta*: Any-Angle Path Planning on Grids
+// https://arxiv.org/abs/1401.3843
+// 2. Lazy Theta*: Any-Angle Path Planning and Path Length Analysis in 3D
+// https://ojs.aaai.org/index.php/AAAI/article/view/7566
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use super::{Grid, Position, Search, SearchStatus};
+
+/// How a search explored the grid, for drawing it once the search is done.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchTrace {
+    /// How many nodes had been expanded before each node was expanded for the first time.
+    pub expansion_order: HashMap<Position, usize>,
+    /// The lowest g cost each node was expanded with, for searches that keep one.
+    pub g_costs: HashMap<Position, i32>,
+    /// The open set when the trace was taken.
+    pub frontier: Vec<Position>,
+}
+
+/// Records the `SearchTrace` of a search while it is being stepped, so that searches which run on
+/// their own, like `find_path`, don't pay for it. Searches that expand a node more than once, like
+/// IDDFS in every iteration, keep the order of its first expansion.
+#[derive(Debug)]
+pub struct TracedSearch<S: Search + ?Sized = dyn Search> {
+    expansion_order: HashMap<Position, usize>,
+    g_costs: HashMap<Position, i32>,
+    search: Box<S>,
+}
+
+impl<S: Search + ?Sized> TracedSearch<S> {
+    #[must_use]
+    pub fn new(search: Box<S>) -> Self {
+        TracedSearch {
+            expansion_order: HashMap::new(),
+            g_costs: HashMap::new(),
+            search,
+        }
+    }
+
+    #[must_use]
+    pub fn trace(&self) -> SearchTrace {
+        SearchTrace {
+            expansion_order: self.expansion_order.clone(),
+            g_costs: self.g_costs.clone(),
+            frontier: self.search.open_set(),
+        }
+    }
+
+    /// The search without the trace, to keep using it after it is done.
+    #[must_use]
+    pub fn into_inner(self) -> Box<S> {
+        self.search
+    }
+}
+
+impl<S: Search + ?Sized> Search for TracedSearch<S> {
+    fn step(&mut self, grid: &Grid) -> SearchStatus {
+        let nodes_expanded = self.search.nodes_expanded();
+        let status = self.search.step(grid);
+        // Steps that only end the search or skip a stale node don't expand anything.
+        if self.search.nodes_expanded() > nodes_expanded {
+            if let Some(position) = self.search.current() {
+                self.expansion_order
+                    .entry(position)
+                    .or_insert(nodes_expanded);
+                if let Some(g_cost) = self.search.g_cost(position) {
+                    let best = self.g_costs.entry(position).or_insert(g_cost);
+                    *best = (*best).min(g_cost);
+                }
+            }
+        }
+        status
+    }
+
+    fn status(&self) -> SearchStatus {
+        self.search.status()
+    }
+
+    fn current(&self) -> Option<Position> {
+        self.search.current()
+    }
+
+    fn open_set(&self) -> Vec<Position> {
+        self.search.open_set()
+    }
+
+    fn closed_set(&self) -> Vec<Position> {
+        self.search.closed_set()
+    }
+
+    fn path(&self) -> Vec<Position> {
+        self.search.path()
+    }
+
+    fn cost(&self) -> Option<i32> {
+        self.search.cost()
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.search.nodes_expanded()
+    }
+
+    fn g_cost(&self, position: Position) -> Option<i32> {
+        self.search.g_cost(position)
+    }
+
+    fn direction(&self, position: Position) -> Option<(i32, i32)> {
+        self.search.direction(position)
+    }
+
+    fn suboptimality_bound(&self) -> Option<f64> {
+        self.search.suboptimality_bound()
+    }
+
+    fn any_angle(&self) -> bool {
+        self.search.any_angle()
+    }
+}
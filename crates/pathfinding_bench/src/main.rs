@@ -0,0 +1,415 @@
+//! Runs every pathfinding algorithm over many start and goal pairs without opening a window, and
+//! prints the nodes expanded, path cost, path length and wall time of each search.
+//!
+//! ```text
+//! cargo run --release -p pathfinding-bench -- --width 128 --height 128 --pairs 200 --format csv
+//! cargo run --release -p pathfinding-bench -- --map arena.map --scenarios arena.map.scen
+//! ```
+//!
+//! Exits with status 1 if any search gave up at `--max-expansions`, so CI notices a search that
+//! stopped finishing.
+
+#![warn(clippy::all, clippy::pedantic)]
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use serde::Serialize;
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use grid_pathfinding::{
+    find_path, load_moving_ai_map, load_moving_ai_scenarios, path_length, Algorithm,
+    DiagonalPolicy, Grid, HierarchicalMap, PathError, PathOptions, PathfindingAlgorithm, Position,
+    CLUSTER_SIZE,
+};
+
+const USAGE: &str = "Usage: pathfinding-bench [options]
+
+Maps:
+  --map <file.map>            Load a Moving AI map instead of generating one
+  --scenarios <file.scen>     Take the start and goal pairs from a Moving AI scenario file
+  --width <n>, --height <n>   Size of the generated map (default 64x64)
+  --density <0..1>            Share of blocked tiles on the generated map (default 0.2)
+  --diagonals <policy>        Diagonal steps on the generated map: never, always,
+                              \"no corner cutting\" or \"only when no obstacles\" (default never)
+  --pairs <n>                 Random start and goal pairs to search (default 100)
+  --seed <n>                  Seed for the generated map and pairs (default 0)
+
+Searches:
+  --algorithms <a,b,...>      Algorithms to run, e.g. astar,dijkstra,\"jump point search\" (default all)
+  --max-expansions <n>        Give up on a search after this many node expansions, searches
+                              that do count as failed (default 1000000)
+
+Output:
+  --format <table|csv|json>   A summary per algorithm, or one record per search (default table)";
+
+// The arguments that are followed by a value.
+const VALUE_ARGS: [&str; 11] = [
+    "--map",
+    "--scenarios",
+    "--width",
+    "--height",
+    "--density",
+    "--diagonals",
+    "--pairs",
+    "--seed",
+    "--algorithms",
+    "--max-expansions",
+    "--format",
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug)]
+struct BenchOptions {
+    map: Option<String>,
+    scenarios: Option<String>,
+    width: i32,
+    height: i32,
+    density: f64,
+    diagonal_policy: DiagonalPolicy,
+    pairs: usize,
+    seed: u64,
+    algorithms: Vec<PathfindingAlgorithm>,
+    max_expansions: usize,
+    format: Format,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            map: None,
+            scenarios: None,
+            width: 64,
+            height: 64,
+            density: 0.2,
+            diagonal_policy: DiagonalPolicy::Never,
+            pairs: 100,
+            seed: 0,
+            algorithms: PathfindingAlgorithm::ALL.to_vec(),
+            // DFS, IDDFS and IDA* can take exponentially long on open maps.
+            max_expansions: 1_000_000,
+            format: Format::Table,
+        }
+    }
+}
+
+/// A single search, as written to CSV and JSON.
+#[derive(Debug, Serialize)]
+struct Record {
+    algorithm: String,
+    start: (i32, i32),
+    goal: (i32, i32),
+    nodes_expanded: usize,
+    cost: Option<i32>,
+    length: Option<f64>,
+    wall_time_us: f64,
+    /// Whether the search hit `--max-expansions` before it finished.
+    gave_up: bool,
+    error: Option<String>,
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{error}\n\n{USAGE}");
+        process::exit(2);
+    });
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let grid = match &options.map {
+        Some(path) => load_moving_ai_map(path).unwrap_or_else(|error| {
+            eprintln!("Could not load {path}: {error}");
+            process::exit(1);
+        }),
+        None => generate_grid(&options, &mut rng),
+    };
+    let pairs = match &options.scenarios {
+        Some(path) => load_moving_ai_scenarios(path)
+            .unwrap_or_else(|error| {
+                eprintln!("Could not load {path}: {error}");
+                process::exit(1);
+            })
+            .iter()
+            .map(|scenario| (scenario.start, scenario.goal))
+            .collect(),
+        None => random_pairs(&grid, options.pairs, &mut rng),
+    };
+    eprintln!(
+        "{} start and goal pairs on a {}x{} map.",
+        pairs.len(),
+        grid.width,
+        grid.height
+    );
+    let path_options = PathOptions {
+        max_expansions: Some(options.max_expansions),
+    };
+    let records = run(&grid, &pairs, &options.algorithms, &path_options);
+    match options.format {
+        Format::Table => print_table(&options.algorithms, &records),
+        Format::Csv => print_csv(&records),
+        Format::Json => match serde_json::to_string_pretty(&records) {
+            Ok(json) => println!("{json}"),
+            Err(error) => {
+                eprintln!("Could not write JSON: {error}");
+                process::exit(1);
+            }
+        },
+    }
+    let gave_up = records.iter().filter(|record| record.gave_up).count();
+    if gave_up > 0 {
+        eprintln!(
+            "{gave_up} searches gave up after {} node expansions.",
+            options.max_expansions
+        );
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<BenchOptions, String> {
+    let mut options = BenchOptions::default();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            println!("{USAGE}");
+            process::exit(0);
+        }
+        if !VALUE_ARGS.contains(&arg.as_str()) {
+            return Err(format!("Unknown argument: {arg}"));
+        }
+        let value = args.next().ok_or_else(|| format!("{arg} needs a value."))?;
+        let invalid = || format!("Invalid value for {arg}: {value}");
+        match arg.as_str() {
+            "--map" => options.map = Some(value),
+            "--scenarios" => options.scenarios = Some(value),
+            "--width" => options.width = parse_positive(&value).ok_or_else(invalid)?,
+            "--height" => options.height = parse_positive(&value).ok_or_else(invalid)?,
+            "--density" => {
+                options.density = value
+                    .parse()
+                    .ok()
+                    .filter(|density| (0.0..1.0).contains(density))
+                    .ok_or_else(invalid)?;
+            }
+            "--diagonals" => {
+                options.diagonal_policy = parse_diagonal_policy(&value).ok_or_else(invalid)?;
+            }
+            "--pairs" => options.pairs = value.parse().map_err(|_| invalid())?,
+            "--seed" => options.seed = value.parse().map_err(|_| invalid())?,
+            "--algorithms" => {
+                options.algorithms = value
+                    .split(',')
+                    .map(|name| {
+                        parse_algorithm(name).ok_or_else(|| format!("Unknown algorithm: {name}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "--max-expansions" => options.max_expansions = value.parse().map_err(|_| invalid())?,
+            "--format" => {
+                options.format = match value.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(invalid()),
+                };
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(options)
+}
+
+fn parse_positive(value: &str) -> Option<i32> {
+    value.parse().ok().filter(|value| *value > 0)
+}
+
+// Matches the displayed names, ignoring case and spaces, e.g. `jumppointsearch` or `d*lite`.
+fn parse_algorithm(name: &str) -> Option<PathfindingAlgorithm> {
+    let normalize = |name: &str| name.replace(' ', "").to_lowercase();
+    PathfindingAlgorithm::ALL
+        .into_iter()
+        .find(|algorithm| normalize(&algorithm.to_string()) == normalize(name))
+}
+
+// Also ignores dashes, so `no-corner-cutting` works without quotes.
+fn parse_diagonal_policy(name: &str) -> Option<DiagonalPolicy> {
+    let normalize = |name: &str| name.replace([' ', '-'], "").to_lowercase();
+    DiagonalPolicy::ALL
+        .into_iter()
+        .find(|policy| normalize(&policy.to_string()) == normalize(name))
+}
+
+fn generate_grid(options: &BenchOptions, rng: &mut StdRng) -> Grid {
+    let mut grid = Grid::new(options.width, options.height, options.diagonal_policy);
+    for blocked in &mut grid.blocked {
+        *blocked = rng.random_bool(options.density);
+    }
+    grid.reset_terrain();
+    grid
+}
+
+// Pairs of open tiles, which may not be connected to each other.
+fn random_pairs(grid: &Grid, pairs: usize, rng: &mut StdRng) -> Vec<(Position, Position)> {
+    let open_tiles: Vec<Position> = (0..grid.height)
+        .flat_map(|y| (0..grid.width).map(move |x| Position(x, y)))
+        .filter(|position| grid.is_walkable(position.0, position.1))
+        .collect();
+    if open_tiles.is_empty() {
+        return Vec::new();
+    }
+    (0..pairs)
+        .map(|_| {
+            let start = open_tiles[rng.random_range(0..open_tiles.len())];
+            let goal = open_tiles[rng.random_range(0..open_tiles.len())];
+            (start, goal)
+        })
+        .collect()
+}
+
+fn run(
+    grid: &Grid,
+    pairs: &[(Position, Position)],
+    algorithms: &[PathfindingAlgorithm],
+    path_options: &PathOptions,
+) -> Vec<Record> {
+    // HPA* reuses one abstract graph for all pairs, like it would in a game.
+    let hierarchical_map = HierarchicalMap::new(grid, CLUSTER_SIZE);
+    let mut records = Vec::new();
+    for pathfinding_algorithm in algorithms {
+        let algorithm: &dyn Algorithm =
+            if *pathfinding_algorithm == PathfindingAlgorithm::HierarchicalAStar {
+                &hierarchical_map
+            } else {
+                pathfinding_algorithm
+            };
+        for (start, goal) in pairs {
+            let started = Instant::now();
+            let result = find_path(grid, *start, *goal, algorithm, path_options);
+            let wall_time_us = started.elapsed().as_secs_f64() * 1_000_000.0;
+            let record = match result {
+                Ok(result) => Record {
+                    algorithm: pathfinding_algorithm.to_string(),
+                    start: (start.0, start.1),
+                    goal: (goal.0, goal.1),
+                    nodes_expanded: result.nodes_expanded,
+                    cost: Some(result.cost),
+                    length: Some(path_length(&result.path)),
+                    wall_time_us,
+                    gave_up: false,
+                    error: None,
+                },
+                Err(error) => Record {
+                    algorithm: pathfinding_algorithm.to_string(),
+                    start: (start.0, start.1),
+                    goal: (goal.0, goal.1),
+                    nodes_expanded: match error {
+                        PathError::NoPath { nodes_expanded } => nodes_expanded,
+                        PathError::ExpansionLimitReached { max_expansions } => max_expansions,
+                        _ => 0,
+                    },
+                    cost: None,
+                    length: None,
+                    wall_time_us,
+                    gave_up: matches!(error, PathError::ExpansionLimitReached { .. }),
+                    error: Some(error.to_string()),
+                },
+            };
+            records.push(record);
+        }
+    }
+    records
+}
+
+fn print_table(algorithms: &[PathfindingAlgorithm], records: &[Record]) {
+    println!(
+        "{:<20} {:>8} {:>8} {:>8} {:>16} {:>12} {:>12} {:>12}",
+        "Algorithm",
+        "Searches",
+        "Solved",
+        "Gave Up",
+        "Nodes Expanded",
+        "Avg Cost",
+        "Avg Length",
+        "Time (ms)"
+    );
+    for pathfinding_algorithm in algorithms {
+        let name = pathfinding_algorithm.to_string();
+        let records: Vec<&Record> = records
+            .iter()
+            .filter(|record| record.algorithm == name)
+            .collect();
+        let solved: Vec<&&Record> = records
+            .iter()
+            .filter(|record| record.error.is_none())
+            .collect();
+        let nodes_expanded: usize = records.iter().map(|record| record.nodes_expanded).sum();
+        let average = |values: Vec<f64>| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>()
+                    / f64::from(u32::try_from(values.len()).unwrap_or(u32::MAX))
+            }
+        };
+        let average_cost = average(
+            solved
+                .iter()
+                .filter_map(|record| record.cost.map(f64::from))
+                .collect(),
+        );
+        let average_length = average(solved.iter().filter_map(|record| record.length).collect());
+        let wall_time_ms = records
+            .iter()
+            .map(|record| record.wall_time_us)
+            .sum::<f64>()
+            / 1000.0;
+        println!(
+            "{:<20} {:>8} {:>8} {:>8} {:>16} {:>12.2} {:>12.2} {:>12.2}",
+            name,
+            records.len(),
+            solved.len(),
+            records.iter().filter(|record| record.gave_up).count(),
+            nodes_expanded,
+            average_cost,
+            average_length,
+            wall_time_ms
+        );
+    }
+}
+
+fn print_csv(records: &[Record]) {
+    println!(
+        "algorithm,start_x,start_y,goal_x,goal_y,nodes_expanded,cost,length,wall_time_us,gave_up,error"
+    );
+    for record in records {
+        println!(
+            "{},{},{},{},{},{},{},{},{:.1},{},{}",
+            csv_field(&record.algorithm),
+            record.start.0,
+            record.start.1,
+            record.goal.0,
+            record.goal.1,
+            record.nodes_expanded,
+            record.cost.map(|cost| cost.to_string()).unwrap_or_default(),
+            record
+                .length
+                .map(|length| format!("{length:.4}"))
+                .unwrap_or_default(),
+            record.wall_time_us,
+            record.gave_up,
+            csv_field(record.error.as_deref().unwrap_or_default())
+        );
+    }
+}
+
+// Quotes fields that contain separators, see https://www.rfc-editor.org/rfc/rfc4180
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
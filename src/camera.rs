@@ -1,13 +1,13 @@
 use bevy::prelude::*;
 
-use super::{MAP_HEIGHT, MAP_WIDTH, TILE_SIZE};
+use super::{Grid, MapConfig};
 
 const CAMERA_MOVEMENT_SPEED: f32 = 10.0;
 
 /// === Startup Systems ===
-pub fn setup_camera(mut commands: Commands) {
-    let x = MAP_WIDTH as f32 / 2.0 * TILE_SIZE as f32;
-    let y = MAP_HEIGHT as f32 / 2.0 * TILE_SIZE as f32;
+pub fn setup_camera(mut commands: Commands, map_config: Res<MapConfig>, map: Res<Grid>) {
+    let x = map.width as f32 / 2.0 * map_config.tile_size as f32;
+    let y = map.height as f32 / 2.0 * map_config.tile_size as f32;
     let position = Transform::from_xyz(x, y, 1000.0);
     commands
         .spawn_bundle(Camera2dBundle {
@@ -0,0 +1,274 @@
+use rand::rngs::StdRng;
+use rand::RngExt;
+use std::collections::VecDeque;
+
+use super::{Grid, Position};
+
+const CAVE_WALL_CHANCE: f64 = 0.45;
+const CAVE_SMOOTHING_STEPS: usize = 5;
+const MIN_LEAF_SIZE: i32 = 10;
+const MIN_ROOM_SIZE: i32 = 4;
+const DRUNKARD_OPEN_SHARE: f64 = 0.4;
+
+// Starts from random noise and repeatedly turns every tile into a wall if most of its neighbours
+// are walls, which smooths the noise into caves. Only the largest cave is kept, so every open
+// tile can reach every other one, and on maps too small for a cave the center is left open.
+pub fn cellular_automata(map: &mut Grid, rng: &mut StdRng) {
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let index = map.xy_idx(x, y);
+            map.blocked[index] = is_border(map, x, y) || rng.random_bool(CAVE_WALL_CHANCE);
+        }
+    }
+    for _ in 0..CAVE_SMOOTHING_STEPS {
+        let previous = map.blocked.clone();
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let mut walls = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let (nx, ny) = (x + dx, y + dy);
+                        // Everything outside of the map counts as a wall.
+                        if (dx, dy) != (0, 0)
+                            && (!map.in_bounds(nx, ny) || previous[map.xy_idx(nx, ny)])
+                        {
+                            walls += 1;
+                        }
+                    }
+                }
+                // Walls stay with 4 walls around them, open tiles need 5 to become one.
+                let index = map.xy_idx(x, y);
+                let wall_threshold = if previous[index] { 4 } else { 5 };
+                map.blocked[index] = is_border(map, x, y) || walls >= wall_threshold;
+            }
+        }
+    }
+    keep_largest_region(map);
+    if map.blocked.iter().all(|blocked| *blocked) {
+        open(map, map.width / 2, map.height / 2);
+    }
+}
+
+// Splits the map in two over and over, puts a room in every part that is too small to split,
+// and joins the two halves of every split with a corridor.
+pub fn bsp_rooms(map: &mut Grid, rng: &mut StdRng) {
+    map.blocked.fill(true);
+    let area = Rect {
+        x: 1,
+        y: 1,
+        width: (map.width - 2).max(1),
+        height: (map.height - 2).max(1),
+    };
+    split(map, rng, area);
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Rect {
+    fn center(&self) -> Position {
+        Position(self.x + self.width / 2, self.y + self.height / 2)
+    }
+}
+
+// Returns the center of one of the rooms in the area, for the corridors to connect to.
+fn split(map: &mut Grid, rng: &mut StdRng, area: Rect) -> Position {
+    let split_vertically = if area.width >= 2 * MIN_LEAF_SIZE && area.height >= 2 * MIN_LEAF_SIZE {
+        rng.random_bool(0.5)
+    } else {
+        area.width >= 2 * MIN_LEAF_SIZE
+    };
+    let (first, second) = if split_vertically {
+        let width = rng.random_range(MIN_LEAF_SIZE..=area.width - MIN_LEAF_SIZE);
+        (
+            Rect { width, ..area },
+            Rect {
+                x: area.x + width,
+                width: area.width - width,
+                ..area
+            },
+        )
+    } else if area.height >= 2 * MIN_LEAF_SIZE {
+        let height = rng.random_range(MIN_LEAF_SIZE..=area.height - MIN_LEAF_SIZE);
+        (
+            Rect { height, ..area },
+            Rect {
+                y: area.y + height,
+                height: area.height - height,
+                ..area
+            },
+        )
+    } else {
+        return carve_room(map, rng, area);
+    };
+    let first_room = split(map, rng, first);
+    let second_room = split(map, rng, second);
+    carve_corridor(map, rng, first_room, second_room);
+    if rng.random_bool(0.5) {
+        first_room
+    } else {
+        second_room
+    }
+}
+
+// A room of random size and position inside the area, leaving a wall between neighbouring rooms.
+fn carve_room(map: &mut Grid, rng: &mut StdRng, area: Rect) -> Position {
+    let max_width = (area.width - 1).max(1);
+    let max_height = (area.height - 1).max(1);
+    let width = rng.random_range(MIN_ROOM_SIZE.min(max_width)..=max_width);
+    let height = rng.random_range(MIN_ROOM_SIZE.min(max_height)..=max_height);
+    let room = Rect {
+        x: area.x + rng.random_range(0..=max_width - width),
+        y: area.y + rng.random_range(0..=max_height - height),
+        width,
+        height,
+    };
+    for y in room.y..room.y + room.height {
+        for x in room.x..room.x + room.width {
+            open(map, x, y);
+        }
+    }
+    room.center()
+}
+
+// An L-shaped corridor, turning either after the horizontal or the vertical part.
+fn carve_corridor(map: &mut Grid, rng: &mut StdRng, from: Position, to: Position) {
+    let corner = if rng.random_bool(0.5) {
+        Position(to.0, from.1)
+    } else {
+        Position(from.0, to.1)
+    };
+    for (a, b) in [(from, corner), (corner, to)] {
+        for x in a.0.min(b.0)..=a.0.max(b.0) {
+            for y in a.1.min(b.1)..=a.1.max(b.1) {
+                open(map, x, y);
+            }
+        }
+    }
+}
+
+// Walks randomly from the center of the map, opening every tile it steps on, until enough of the
+// map is open. Everything it opened is connected through its own path.
+pub fn drunkards_walk(map: &mut Grid, rng: &mut StdRng) {
+    map.blocked.fill(true);
+    let tiles = ((map.width - 2).max(0) * (map.height - 2).max(0)) as usize;
+    let target = (tiles as f64 * DRUNKARD_OPEN_SHARE) as usize;
+    let mut position = Position(map.width / 2, map.height / 2);
+    let mut opened = 0;
+    // Gives up eventually on maps too small to open enough tiles.
+    for _ in 0..tiles * 100 {
+        if opened >= target {
+            break;
+        }
+        let index = map.xy_idx(position.0, position.1);
+        if map.blocked[index] {
+            map.blocked[index] = false;
+            opened += 1;
+        }
+        let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][rng.random_range(0..4)];
+        let next = Position(position.0 + dx, position.1 + dy);
+        if !is_border(map, next.0, next.1) && map.in_bounds(next.0, next.1) {
+            position = next;
+        }
+    }
+}
+
+/// Makes open tiles cost more the closer they are to a wall: `max_cost` right next to one, one
+/// less for every tile further away, down to 1.
+pub fn add_costs_near_walls(map: &mut Grid, max_cost: i32) {
+    // The distance from every tile to the nearest wall, counting diagonal steps as 1.
+    let mut distances: Vec<Option<i32>> = vec![None; map.blocked.len()];
+    let mut queue = VecDeque::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let index = map.xy_idx(x, y);
+            if map.blocked[index] {
+                distances[index] = Some(0);
+                queue.push_back(Position(x, y));
+            }
+        }
+    }
+    while let Some(position) = queue.pop_front() {
+        let distance = distances[map.xy_idx(position.0, position.1)].unwrap_or(0);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (x, y) = (position.0 + dx, position.1 + dy);
+                if map.in_bounds(x, y) && distances[map.xy_idx(x, y)].is_none() {
+                    distances[map.xy_idx(x, y)] = Some(distance + 1);
+                    queue.push_back(Position(x, y));
+                }
+            }
+        }
+    }
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let index = map.xy_idx(x, y);
+            if !map.blocked[index] {
+                // A map without walls has no distances.
+                let distance = distances[index].unwrap_or(max_cost);
+                map.set_cost(Position(x, y), (max_cost + 1 - distance).max(1));
+            }
+        }
+    }
+}
+
+fn is_border(map: &Grid, x: i32, y: i32) -> bool {
+    x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1
+}
+
+fn open(map: &mut Grid, x: i32, y: i32) {
+    if map.in_bounds(x, y) {
+        let index = map.xy_idx(x, y);
+        map.blocked[index] = false;
+    }
+}
+
+// Blocks every open tile that isn't connected to the largest open region.
+fn keep_largest_region(map: &mut Grid) {
+    let mut regions: Vec<Option<usize>> = vec![None; map.blocked.len()];
+    let mut region_sizes = Vec::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let index = map.xy_idx(x, y);
+            if map.blocked[index] || regions[index].is_some() {
+                continue;
+            }
+            let region = region_sizes.len();
+            let mut size = 0;
+            regions[index] = Some(region);
+            let mut stack = vec![Position(x, y)];
+            while let Some(position) = stack.pop() {
+                size += 1;
+                // Only straight steps, so the region stays connected under any diagonal policy.
+                for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    if !map.can_step(position, (dx, dy)) {
+                        continue;
+                    }
+                    let successor = Position(position.0 + dx, position.1 + dy);
+                    let successor_index = map.xy_idx(successor.0, successor.1);
+                    if regions[successor_index].is_none() {
+                        regions[successor_index] = Some(region);
+                        stack.push(successor);
+                    }
+                }
+            }
+            region_sizes.push(size);
+        }
+    }
+    let largest_region = (0..region_sizes.len()).max_by_key(|region| region_sizes[*region]);
+    for (blocked, region) in map.blocked.iter_mut().zip(regions) {
+        if region.is_some() && region != largest_region {
+            *blocked = true;
+        }
+    }
+}
+
+// References
+// 1. https://www.roguebasin.com/index.php/Cellular_Automata_Method_for_Generating_Random_Cave-Like_Levels
+// 2. https://www.roguebasin.com/index.php/Basic_BSP_Dungeon_generation
+// 3. https://www.roguebasin.com/index.php/Random_Walk_Cave_Generation
@@ -1,17 +1,19 @@
 use bevy::prelude::*;
-use pathfinding::prelude::{astar, bfs, dijkstra};
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
 
 use super::{
-    world_position_to_index, Map, MapUpdatedEvent, Mouse, Position, UserInterfaceInteractionEvent,
+    find_path, find_path_traced, path_result, select_algorithm, world_position_to_index, DStarLite,
+    DiagonalPolicy, FlowField, Grid, Heuristic, HeuristicAlgorithm, HierarchicalMap, MapConfig,
+    MapUpdatedEvent, Mouse, MovementProfile, PathError, PathOptions, PathResult,
+    PathfindingAlgorithm, PathfindingBudget, PathfindingSettings, Position, Search, SearchStatus,
+    SearchTrace, TerrainId, TerrainRegistry, TracedSearch, UserInterfaceInteractionEvent,
+    DEFAULT_WEIGHT,
 };
 
-#[derive(Clone, Copy, Debug)]
-pub enum PathfindingAlgorithm {
-    AStar,
-    BFS,
-    Dijkstra,
-}
-
 #[derive(Debug)]
 pub enum PlacementMode {
     Path,
@@ -20,17 +22,173 @@ pub enum PlacementMode {
     Goal,
     IncreaseCost,
     DecreaseCost,
+    Terrain(TerrainId),
+}
+
+// What the heatmap colors the tiles the last search expanded by, BFS floods outwards evenly while
+// A* heads straight for the goal.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HeatmapMode {
+    Off,
+    ExpansionOrder,
+    GCost,
+}
+
+impl HeatmapMode {
+    pub const ALL: [HeatmapMode; 3] = [
+        HeatmapMode::Off,
+        HeatmapMode::ExpansionOrder,
+        HeatmapMode::GCost,
+    ];
+}
+
+impl fmt::Display for HeatmapMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HeatmapMode::Off => "Off",
+            HeatmapMode::ExpansionOrder => "Expansion Order",
+            HeatmapMode::GCost => "G Cost",
+        };
+        write!(f, "{name}")
+    }
 }
 
 // === Resources ===
 #[derive(Debug)]
 pub struct GameState {
     pub pathfinding_algorithm: PathfindingAlgorithm,
+    // Followed by every algorithm for which `HeuristicAlgorithm::uses_heuristic` is true: A*,
+    // bidirectional A*, Jump Point Search, weighted A*, ARA*, greedy best-first search and IDA*.
+    pub heuristic: Heuristic,
+    // The epsilon of weighted A* and the first path of ARA*.
+    pub weight: f64,
+    // How many times more than the cheapest path the ARA* path may cost, while it is improving it.
+    pub suboptimality_bound: Option<f64>,
     pub placement_mode: PlacementMode,
+    // The terrain `PlacementMode::Terrain` paints with, kept while other modes are selected.
+    pub terrain_brush: TerrainId,
+    // How the unit being solved for moves, every search runs on the map as it sees it.
+    pub movement_profile: MovementProfile,
     pub start: Position,
     pub goal: Position,
     pub path: Vec<Position>,
-    pub step: usize,
+    pub search: Option<TracedSearch>,
+    // How the last solve explored the map, stepped searches are traced as they go instead.
+    pub trace: Option<SearchTrace>,
+    // The last flow field that was solved, so its arrows stay on the map.
+    pub flow_field: Option<FlowField>,
+    pub heatmap_mode: HeatmapMode,
+}
+
+impl GameState {
+    #[must_use]
+    pub fn heuristic_algorithm(&self) -> HeuristicAlgorithm {
+        HeuristicAlgorithm {
+            pathfinding_algorithm: self.pathfinding_algorithm,
+            heuristic: self.heuristic,
+            weight: self.weight,
+        }
+    }
+}
+
+// The profiles that can be picked, and the last path every profile found on the current map, so
+// the paths of different units can be compared.
+#[derive(Debug)]
+pub struct MovementProfiles {
+    pub profiles: Vec<MovementProfile>,
+    pub selected: usize,
+    pub paths: Vec<Vec<Position>>,
+}
+
+impl Default for MovementProfiles {
+    fn default() -> Self {
+        let profiles = MovementProfile::defaults();
+        MovementProfiles {
+            paths: vec![Vec::new(); profiles.len()],
+            profiles,
+            selected: 0,
+        }
+    }
+}
+
+impl MovementProfiles {
+    #[must_use]
+    pub fn selected(&self) -> &MovementProfile {
+        &self.profiles[self.selected]
+    }
+}
+
+// Kept between solves so that D* Lite can repair its path while the map is being edited.
+#[derive(Debug, Default)]
+pub struct Replanner {
+    pub d_star_lite: Option<DStarLite>,
+    // What the last full solve expanded, which every repair is reported next to.
+    pub full_solve_nodes_expanded: usize,
+    // Also solves from scratch after every repair to compare the nodes expanded, toggled with F3.
+    pub compare_with_full_solve: bool,
+}
+
+// WASM builds have no threads to solve on.
+const TIME_SLICED: bool = cfg!(target_arch = "wasm32");
+
+// Runs solves on the AsyncComputeTaskPool, so that large maps don't stall the frame. Time-sliced
+// solves step the search within the `PathfindingBudget` every frame instead.
+#[derive(Debug)]
+pub struct Solver {
+    pub time_sliced: bool,
+    pub pending: Option<PendingSolve>,
+    pub optimality_check: Option<OptimalityCheck>,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver {
+            time_sliced: TIME_SLICED,
+            pending: None,
+            optimality_check: None,
+        }
+    }
+}
+
+// Paths found on the main thread, by stepping or time-sliced solves, are checked against Dijkstra
+// on the AsyncComputeTaskPool, so that the check doesn't stall the frame either.
+#[derive(Debug)]
+pub struct OptimalityCheck {
+    cost: i32,
+    cheapest_cost: Task<Option<i32>>,
+}
+
+#[derive(Debug)]
+pub struct PendingSolve {
+    job: SolveJob,
+    // What the solve was started from, it is discarded once the game no longer matches it.
+    map: Arc<Grid>,
+    // The map as the profile sees it, which time-sliced solves are stepped on.
+    grid: Arc<Grid>,
+    profile: MovementProfile,
+    start: Position,
+    goal: Position,
+    frames: u32,
+}
+
+#[derive(Debug)]
+enum SolveJob {
+    Task(Task<SolveOutput>),
+    TimeSliced(TracedSearch),
+    // Kept apart, so that the planner can be handed to the `Replanner` once it is done.
+    TimeSlicedDStarLite(TracedSearch<DStarLite>),
+    // Kept apart, so that its directions can be drawn once it is done.
+    TimeSlicedFlowField(TracedSearch<FlowField>),
+}
+
+#[derive(Debug)]
+struct SolveOutput {
+    result: Result<PathResult, PathError>,
+    trace: SearchTrace,
+    d_star_lite: Option<DStarLite>,
+    flow_field: Option<FlowField>,
+    // Found by Dijkstra on the solve task, to tell whether the path is optimal.
+    cheapest_cost: Option<i32>,
 }
 
 // === Events ===
@@ -46,6 +204,26 @@ pub struct CycleAlgorithmLeftEvent {}
 
 pub struct CycleAlgorithmRightEvent {}
 
+pub struct CycleTerrainLeftEvent {}
+
+pub struct CycleTerrainRightEvent {}
+
+pub struct CycleProfileLeftEvent {}
+
+pub struct CycleProfileRightEvent {}
+
+pub struct CycleDiagonalPolicyEvent {}
+
+pub struct CycleHeuristicLeftEvent {}
+
+pub struct CycleHeuristicRightEvent {}
+
+pub struct DecreaseWeightEvent {}
+
+pub struct IncreaseWeightEvent {}
+
+pub struct CycleHeatmapModeEvent {}
+
 pub struct PathfindingAlgorithmSelectionChangedEvent {
     pub pathfinding_algorithm: PathfindingAlgorithm,
 }
@@ -57,16 +235,27 @@ pub fn setup_game(
     mut commands: Commands,
     mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mut pathfinding_algorithm_changed_event: EventWriter<PathfindingAlgorithmChangedEvent>,
+    map: Res<Grid>,
 ) {
     println!("Setup Game...");
     commands.insert_resource(GameState {
         pathfinding_algorithm: PathfindingAlgorithm::BFS,
+        heuristic: Heuristic::Manhattan,
+        weight: DEFAULT_WEIGHT,
+        suboptimality_bound: None,
         placement_mode: PlacementMode::Obstacle,
-        start: Position(16, 32),
-        goal: Position(48, 32),
+        terrain_brush: TerrainId::GRASS,
+        movement_profile: MovementProfile::default(),
+        start: default_start(&map),
+        goal: default_goal(&map),
         path: Vec::new(),
-        step: 0,
+        search: None,
+        trace: None,
+        flow_field: None,
+        heatmap_mode: HeatmapMode::Off,
     });
+    commands.insert_resource(Replanner::default());
+    commands.insert_resource(Solver::default());
     map_updated_event_writer.send(MapUpdatedEvent {});
     pathfinding_algorithm_changed_event.send(PathfindingAlgorithmChangedEvent {});
 }
@@ -75,15 +264,17 @@ pub fn placement_system(
     mut user_interface_interaction_event_reader: EventReader<UserInterfaceInteractionEvent>,
     mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mouse: Res<Mouse>,
+    map_config: Res<MapConfig>,
+    terrains: Res<TerrainRegistry>,
     mut game_state: ResMut<GameState>,
-    mut map: ResMut<Map>,
+    mut map: ResMut<Grid>,
 ) {
     // This is a hack to prevent placement when buttons are clicked.
     for _ in user_interface_interaction_event_reader.iter() {
         return;
     }
     if mouse.holding_lmb {
-        let (x, y) = world_position_to_index(mouse.world_position);
+        let (x, y) = world_position_to_index(mouse.world_position, map_config.tile_size);
         let clicked_position = Position(x, y);
         // Prevent placing on start or goal:
         if clicked_position == game_state.start || clicked_position == game_state.goal {
@@ -92,12 +283,13 @@ pub fn placement_system(
         // println!("clicked index x: {}, y: {}", x, y);
         match game_state.placement_mode {
             PlacementMode::Path => {
-                let index = map.xy_idx(x, y);
-                map.blocked[index] = false;
+                map.set_terrain(clicked_position, TerrainId::FLOOR, &terrains);
             }
             PlacementMode::Obstacle => {
-                let index = map.xy_idx(x, y);
-                map.blocked[index] = true;
+                map.set_terrain(clicked_position, TerrainId::WALL, &terrains);
+            }
+            PlacementMode::Terrain(terrain) => {
+                map.set_terrain(clicked_position, terrain, &terrains);
             }
             PlacementMode::Start => {
                 game_state.start = Position(x, y);
@@ -110,6 +302,9 @@ pub fn placement_system(
             }
         }
         game_state.path = Vec::new();
+        game_state.search = None;
+        game_state.trace = None;
+        game_state.flow_field = None;
         map_updated_event_writer.send(MapUpdatedEvent {});
     }
 }
@@ -118,15 +313,16 @@ pub fn cost_system(
     mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mouse: Res<Mouse>,
     mouse_input: Res<Input<MouseButton>>,
+    map_config: Res<MapConfig>,
     mut game_state: ResMut<GameState>,
-    mut map: ResMut<Map>,
+    mut map: ResMut<Grid>,
 ) {
     // This is a hack to prevent placement when buttons are clicked.
     for _ in user_interface_interaction_event_reader.iter() {
         return;
     }
     if mouse_input.just_pressed(MouseButton::Left) {
-        let (x, y) = world_position_to_index(mouse.world_position);
+        let (x, y) = world_position_to_index(mouse.world_position, map_config.tile_size);
         let clicked_position = Position(x, y);
         // Prevent placing on start or goal:
         if clicked_position == game_state.start || clicked_position == game_state.goal {
@@ -136,14 +332,14 @@ pub fn cost_system(
             PlacementMode::IncreaseCost => {
                 let index = map.xy_idx(x, y);
                 if let Some(current_cost) = map.costs[index] {
-                    map.costs[index] = Some(current_cost + 1);
+                    map.set_cost(clicked_position, current_cost + 1);
                 }
             }
             PlacementMode::DecreaseCost => {
                 let index = map.xy_idx(x, y);
                 if let Some(current_cost) = map.costs[index] {
                     if current_cost > 1 {
-                        map.costs[index] = Some(current_cost - 1);
+                        map.set_cost(clicked_position, current_cost - 1);
                     }
                 }
             }
@@ -152,108 +348,491 @@ pub fn cost_system(
             }
         }
         game_state.path = Vec::new();
+        game_state.search = None;
+        game_state.trace = None;
+        game_state.flow_field = None;
         map_updated_event_writer.send(MapUpdatedEvent {});
     }
 }
 
+// Advances the search by a single node expansion, starting a new one if needed.
 pub fn step_system(
     mut step_event_reader: EventReader<StepEvent>,
     mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mut game_state: ResMut<GameState>,
+    mut solver: ResMut<Solver>,
+    mut hierarchical_map: ResMut<HierarchicalMap>,
+    map: Res<Grid>,
 ) {
     for _ in step_event_reader.iter() {
-        if !game_state.path.is_empty() {
-            if game_state.step < game_state.path.len() - 1 {
-                game_state.step = game_state.step + 1;
-            } else {
-                game_state.step = 2; // Why 2? Me Dumb?
+        let game_state = &mut *game_state;
+        let map = game_state.movement_profile.apply(&map);
+        let search_finished = game_state
+            .search
+            .as_ref()
+            .is_none_or(|search| search.status() != SearchStatus::Searching);
+        if search_finished {
+            game_state.path = Vec::new();
+            game_state.trace = None;
+            game_state.flow_field = None;
+            let algorithm = game_state.heuristic_algorithm();
+            warn_if_inconsistent_heuristic(&algorithm, map.diagonal_policy);
+            let algorithm = select_algorithm(&algorithm, &map, &mut hierarchical_map);
+            game_state.search =
+                match algorithm.start_search(&map, game_state.start, game_state.goal) {
+                    Ok(search) => Some(TracedSearch::new(search)),
+                    Err(error) => {
+                        println!("{error}");
+                        None
+                    }
+                };
+        }
+        if let Some(search) = game_state.search.as_mut() {
+            if search.step(&map) != SearchStatus::Searching {
+                let result = path_result(search);
+                print_path_result(&result);
+                solver.optimality_check = check_optimality(&result, Arc::new(map.clone()));
+            }
+            // ARA* already has a path while it is still improving it.
+            game_state.path = search.path();
+            game_state.suboptimality_bound = search.suboptimality_bound();
+        }
+        // A finished flow field is kept like after a solve, so its arrows stay on the map.
+        if game_state.pathfinding_algorithm == PathfindingAlgorithm::FlowField
+            && game_state
+                .search
+                .as_ref()
+                .is_some_and(|search| search.status() != SearchStatus::Searching)
+        {
+            if let Some(search) = game_state.search.take() {
+                game_state.trace = Some(search.trace());
+                let search: Box<dyn Any> = search.into_inner();
+                game_state.flow_field = search.downcast::<FlowField>().ok().map(|field| *field);
             }
-            map_updated_event_writer.send(MapUpdatedEvent {});
         }
+        map_updated_event_writer.send(MapUpdatedEvent {});
     }
 }
 
-// See Reference 1
 pub fn solve_system(
     mut solve_event_reader: EventReader<SolveEvent>,
-    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mut game_state: ResMut<GameState>,
-    map: Res<Map>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
+    mut hierarchical_map: ResMut<HierarchicalMap>,
+    settings: Res<PathfindingSettings>,
+    map: Res<Grid>,
 ) {
     for _ in solve_event_reader.iter() {
-        println!("Attempting to solve...");
+        println!(
+            "Attempting to solve as {}...",
+            game_state.movement_profile.name
+        );
+        game_state.search = None;
+        game_state.trace = None;
+        game_state.flow_field = None;
+        replanner.d_star_lite = None;
+
+        let snapshot = Arc::new(map.clone());
+        let profiled = Arc::new(game_state.movement_profile.apply(&map));
+        let grid = Arc::clone(&profiled);
+        let algorithm = game_state.heuristic_algorithm();
+        let pathfinding_algorithm = algorithm.pathfinding_algorithm;
+        warn_if_inconsistent_heuristic(&algorithm, profiled.diagonal_policy);
         let start = game_state.start;
         let goal = game_state.goal;
-        match game_state.pathfinding_algorithm {
-            PathfindingAlgorithm::AStar => {
-                let result = astar(
-                    &start,
-                    |position| {
-                        map.get_successors(position, map.allow_diagonals)
-                            .iter()
-                            .map(|successor| (successor.position, successor.cost))
-                            .collect::<Vec<_>>()
-                    },
-                    |position| position.distance(&goal),
-                    |position| *position == goal,
-                );
-                if let Some(result) = result {
-                    println!("Path: {:?}", result.0);
-                    println!("Cost: {:?}", result.1);
-                    game_state.path = result.0;
-                    game_state.step = game_state.path.len();
-                } else {
-                    println!("No Path Found!");
-                    game_state.path = Vec::new();
-                    game_state.step = 0;
+        let options = settings.options;
+        let job = if pathfinding_algorithm == PathfindingAlgorithm::DStarLite && solver.time_sliced
+        {
+            let d_star_lite = DStarLite::new(&profiled, start, goal);
+            SolveJob::TimeSlicedDStarLite(TracedSearch::new(Box::new(d_star_lite)))
+        } else if pathfinding_algorithm == PathfindingAlgorithm::FlowField && solver.time_sliced {
+            let flow_field = FlowField::new(&profiled, start, goal);
+            SolveJob::TimeSlicedFlowField(TracedSearch::new(Box::new(flow_field)))
+        } else if solver.time_sliced || pathfinding_algorithm == PathfindingAlgorithm::ARAStar {
+            // ARA* is always time-sliced, so that every better path it finds is shown right away.
+            let algorithm = select_algorithm(&algorithm, &profiled, &mut hierarchical_map);
+            match algorithm.start_search(&profiled, start, goal) {
+                Ok(search) => SolveJob::TimeSliced(TracedSearch::new(search)),
+                Err(error) => {
+                    println!("{error}");
+                    continue;
                 }
             }
-            PathfindingAlgorithm::BFS => {
-                let result = bfs(
-                    &start,
-                    |position| {
-                        map.get_successors(position, map.allow_diagonals)
-                            .iter()
-                            .map(|successor| successor.position)
-                            .collect::<Vec<_>>()
-                    },
-                    |position| *position == goal,
-                );
-                if let Some(result) = result {
-                    println!("Path: {:?}", result);
-                    game_state.path = result;
-                    game_state.step = game_state.path.len();
+        } else {
+            // HPA* gets a copy of the abstract graph that is kept up to date here.
+            let hierarchical_map =
+                (pathfinding_algorithm == PathfindingAlgorithm::HierarchicalAStar).then(|| {
+                    hierarchical_map.update(&profiled);
+                    hierarchical_map.clone()
+                });
+            SolveJob::Task(AsyncComputeTaskPool::get().spawn(async move {
+                let mut output = if pathfinding_algorithm == PathfindingAlgorithm::DStarLite {
+                    let d_star_lite = DStarLite::new(&grid, start, goal);
+                    let mut d_star_lite = TracedSearch::new(Box::new(d_star_lite));
+                    d_star_lite.run(&grid);
+                    SolveOutput {
+                        result: path_result(&d_star_lite),
+                        trace: d_star_lite.trace(),
+                        d_star_lite: Some(*d_star_lite.into_inner()),
+                        flow_field: None,
+                        cheapest_cost: None,
+                    }
+                } else if pathfinding_algorithm == PathfindingAlgorithm::FlowField {
+                    let flow_field = FlowField::new(&grid, start, goal);
+                    let mut flow_field = TracedSearch::new(Box::new(flow_field));
+                    flow_field.run(&grid);
+                    SolveOutput {
+                        result: path_result(&flow_field),
+                        trace: flow_field.trace(),
+                        d_star_lite: None,
+                        flow_field: Some(*flow_field.into_inner()),
+                        cheapest_cost: None,
+                    }
                 } else {
-                    println!("No Path Found!");
-                    game_state.path = Vec::new();
-                    game_state.step = 0;
+                    let (result, trace) = match &hierarchical_map {
+                        Some(hierarchical_map) => {
+                            find_path_traced(&grid, start, goal, hierarchical_map, &options)
+                        }
+                        None => find_path_traced(&grid, start, goal, &algorithm, &options),
+                    };
+                    SolveOutput {
+                        result,
+                        trace,
+                        d_star_lite: None,
+                        flow_field: None,
+                        cheapest_cost: None,
+                    }
+                };
+                output.cheapest_cost = match &output.result {
+                    Ok(result) if pathfinding_algorithm == PathfindingAlgorithm::Dijkstra => {
+                        Some(result.cost)
+                    }
+                    Ok(_) => cheapest_cost(&grid, start, goal),
+                    Err(_) => None,
+                };
+                output
+            }))
+        };
+        // Replacing a pending solve or check drops its task, which cancels it.
+        solver.optimality_check = None;
+        solver.pending = Some(PendingSolve {
+            job,
+            map: snapshot,
+            grid: profiled,
+            profile: game_state.movement_profile.clone(),
+            start,
+            goal,
+            frames: 0,
+        });
+    }
+}
+
+// Applies the result of the pending solve once it is done, unless the map, start or goal changed
+// while it was running.
+pub fn poll_solve_system(
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut game_state: ResMut<GameState>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
+    mut pathfinding_budget: ResMut<PathfindingBudget>,
+    settings: Res<PathfindingSettings>,
+    map: Res<Grid>,
+) {
+    // Checked through `Deref` first, so that the solver is only marked as changed while solving.
+    if solver.pending.is_none() {
+        return;
+    }
+    let Some(pending) = solver.pending.as_mut() else {
+        return;
+    };
+    // The map is only compared with the snapshot after it was edited, since that is expensive.
+    if (map.is_changed() && *pending.map != *map)
+        || pending.start != game_state.start
+        || pending.goal != game_state.goal
+        || pending.profile != game_state.movement_profile
+    {
+        println!("The map changed while solving, discarded the solve.");
+        solver.pending = None;
+        return;
+    }
+    pending.frames += 1;
+    let output = match &mut pending.job {
+        SolveJob::Task(task) => future::block_on(future::poll_once(task)),
+        SolveJob::TimeSliced(search) => {
+            let output = pathfinding_budget
+                .step(search, &pending.grid, &settings.options)
+                .map(|result| SolveOutput {
+                    result,
+                    trace: search.trace(),
+                    d_star_lite: None,
+                    flow_field: None,
+                    cheapest_cost: None,
+                });
+            // ARA* has a path while it is still improving it, every better one is shown right away.
+            if output.is_none() && search.cost().is_some() && search.path() != game_state.path {
+                let suboptimality_bound = search.suboptimality_bound();
+                game_state.path = search.path();
+                game_state.trace = Some(search.trace());
+                game_state.suboptimality_bound = suboptimality_bound;
+                if let Some(bound) = suboptimality_bound {
+                    println!("Found a path within {bound:.2} times the cheapest, improving it...");
                 }
+                map_updated_event_writer.send(MapUpdatedEvent {});
             }
-            PathfindingAlgorithm::Dijkstra => {
-                let result = dijkstra(
-                    &start,
-                    |position| {
-                        map.get_successors(position, map.allow_diagonals)
-                            .iter()
-                            .map(|successor| (successor.position, successor.cost))
-                            .collect::<Vec<_>>()
-                    },
-                    |position| *position == goal,
-                );
-                if let Some(result) = result {
-                    println!("Path: {:?}", result.0);
-                    println!("Cost: {:?}", result.1);
-                    game_state.path = result.0;
-                    game_state.step = game_state.path.len();
-                } else {
-                    println!("No Path Found!");
-                    game_state.path = Vec::new();
-                    game_state.step = 0;
-                }
+            output
+        }
+        SolveJob::TimeSlicedDStarLite(d_star_lite) => pathfinding_budget
+            .step(d_star_lite, &pending.grid, &settings.options)
+            .map(|result| SolveOutput {
+                result,
+                trace: d_star_lite.trace(),
+                d_star_lite: None,
+                flow_field: None,
+                cheapest_cost: None,
+            }),
+        SolveJob::TimeSlicedFlowField(flow_field) => pathfinding_budget
+            .step(flow_field, &pending.grid, &settings.options)
+            .map(|result| SolveOutput {
+                result,
+                trace: flow_field.trace(),
+                d_star_lite: None,
+                flow_field: None,
+                cheapest_cost: None,
+            }),
+    };
+    let Some(mut output) = output else {
+        return;
+    };
+    let frames = pending.frames;
+    let grid = Arc::clone(&pending.grid);
+    game_state.suboptimality_bound = match &pending.job {
+        SolveJob::TimeSliced(search) => search.suboptimality_bound(),
+        _ => None,
+    };
+    print_path_result(&output.result);
+    match solver.pending.take().map(|pending| pending.job) {
+        Some(SolveJob::Task(_)) => {
+            if let Ok(result) = &output.result {
+                print_optimality(result.cost, output.cheapest_cost);
+            }
+        }
+        Some(SolveJob::TimeSlicedDStarLite(d_star_lite)) => {
+            output.d_star_lite = Some(*d_star_lite.into_inner());
+            solver.optimality_check = check_optimality(&output.result, grid);
+        }
+        Some(SolveJob::TimeSlicedFlowField(flow_field)) => {
+            output.flow_field = Some(*flow_field.into_inner());
+            solver.optimality_check = check_optimality(&output.result, grid);
+        }
+        Some(SolveJob::TimeSliced(_)) | None => {
+            solver.optimality_check = check_optimality(&output.result, grid);
+        }
+    }
+    println!("Solved in {frames} frames.");
+    game_state.path = output.result.map(|result| result.path).unwrap_or_default();
+    game_state.trace = Some(output.trace);
+    game_state.flow_field = output.flow_field;
+    replanner.full_solve_nodes_expanded = output
+        .d_star_lite
+        .as_ref()
+        .map_or(0, DStarLite::nodes_expanded);
+    replanner.d_star_lite = output.d_star_lite;
+    map_updated_event_writer.send(MapUpdatedEvent {});
+}
+
+// Repairs the D* Lite path after the map, start or goal changed, instead of solving from scratch.
+pub fn replan_system(
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut game_state: ResMut<GameState>,
+    mut replanner: ResMut<Replanner>,
+    map: Res<Grid>,
+) {
+    if !map.is_changed() && !game_state.is_changed() {
+        return;
+    }
+    let Replanner {
+        d_star_lite: Some(d_star_lite),
+        full_solve_nodes_expanded,
+        compare_with_full_solve,
+    } = &mut *replanner
+    else {
+        return;
+    };
+    let map = game_state.movement_profile.apply(&map);
+    if let Some(nodes_reexpanded) = d_star_lite.repair(&map, game_state.start, game_state.goal) {
+        if *compare_with_full_solve {
+            let mut full_solve = DStarLite::new(&map, game_state.start, game_state.goal);
+            full_solve.run(&map);
+            *full_solve_nodes_expanded = full_solve.nodes_expanded();
+            println!(
+                "Repaired path: re-expanded {nodes_reexpanded} nodes, a full re-solve expands {full_solve_nodes_expanded}."
+            );
+        } else {
+            println!(
+                "Repaired path: re-expanded {nodes_reexpanded} nodes, the last full solve expanded {full_solve_nodes_expanded}."
+            );
+        }
+    }
+    let path = d_star_lite.path();
+    if game_state.path != path {
+        game_state.path = path;
+        map_updated_event_writer.send(MapUpdatedEvent {});
+    }
+}
+
+// Solving from scratch after every repair costs more than the repair itself, so it is only done
+// while comparing the two.
+pub fn replan_comparison_keyboard_system(
+    keyboard: Res<Input<KeyCode>>,
+    mut replanner: ResMut<Replanner>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        replanner.compare_with_full_solve = !replanner.compare_with_full_solve;
+        println!(
+            "Compare D* Lite repairs with a full re-solve: {}",
+            if replanner.compare_with_full_solve {
+                "On"
+            } else {
+                "Off"
             }
+        );
+    }
+}
+
+// Keeps the last path of the selected profile, the paths of every profile are only worth
+// comparing on the map they were found on.
+pub fn update_profile_paths_system(
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut movement_profiles: ResMut<MovementProfiles>,
+    game_state: Res<GameState>,
+    map: Res<Grid>,
+) {
+    if map.is_changed() && movement_profiles.paths.iter().any(|path| !path.is_empty()) {
+        for path in &mut movement_profiles.paths {
+            path.clear();
         }
         map_updated_event_writer.send(MapUpdatedEvent {});
     }
+    let selected = movement_profiles.selected;
+    if game_state.is_changed()
+        && !game_state.path.is_empty()
+        && movement_profiles.paths[selected] != game_state.path
+    {
+        movement_profiles.paths[selected].clone_from(&game_state.path);
+    }
+}
+
+// Switches to the next diagonal policy, the current path is reset since it may no longer be allowed.
+pub fn cycle_diagonal_policy_system(
+    mut cycle_diagonal_policy_event_reader: EventReader<CycleDiagonalPolicyEvent>,
+    mut reset_event_writer: EventWriter<ResetEvent>,
+    mut map: ResMut<Grid>,
+) {
+    for _ in cycle_diagonal_policy_event_reader.iter() {
+        let index = DiagonalPolicy::ALL
+            .iter()
+            .position(|policy| *policy == map.diagonal_policy)
+            .unwrap_or(0);
+        map.diagonal_policy = DiagonalPolicy::ALL[(index + 1) % DiagonalPolicy::ALL.len()];
+        println!("Diagonal policy: {}", map.diagonal_policy);
+        reset_event_writer.send(ResetEvent {});
+    }
+}
+
+// Switches to the next heatmap mode, the heatmap is redrawn from the trace of the last search.
+pub fn cycle_heatmap_mode_system(
+    mut cycle_heatmap_mode_event_reader: EventReader<CycleHeatmapModeEvent>,
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut game_state: ResMut<GameState>,
+) {
+    for _ in cycle_heatmap_mode_event_reader.iter() {
+        let index = HeatmapMode::ALL
+            .iter()
+            .position(|mode| *mode == game_state.heatmap_mode)
+            .unwrap_or(0);
+        game_state.heatmap_mode = HeatmapMode::ALL[(index + 1) % HeatmapMode::ALL.len()];
+        println!("Heatmap: {}", game_state.heatmap_mode);
+        map_updated_event_writer.send(MapUpdatedEvent {});
+    }
+}
+
+// A* can miss the cheapest path when its heuristic overestimates the cost of a step.
+pub fn warn_if_inconsistent_heuristic(
+    algorithm: &HeuristicAlgorithm,
+    diagonal_policy: DiagonalPolicy,
+) {
+    if algorithm.uses_heuristic() && !algorithm.heuristic.is_consistent(diagonal_policy) {
+        println!(
+            "Warning: the {} heuristic is inconsistent with diagonal steps ({}), {} may not find the cheapest path.",
+            algorithm.heuristic, diagonal_policy, algorithm.pathfinding_algorithm
+        );
+    }
+}
+
+// The start and goal sit a quarter of the way in from either side, on any map size.
+fn default_start(map: &Grid) -> Position {
+    Position(map.width / 4, map.height / 2)
+}
+
+fn default_goal(map: &Grid) -> Position {
+    Position(map.width * 3 / 4, map.height / 2)
+}
+
+fn print_path_result(result: &Result<PathResult, PathError>) {
+    match result {
+        Ok(result) => {
+            println!("Path: {:?}", result.path);
+            println!("Cost: {}", result.cost);
+            println!("Nodes Expanded: {}", result.nodes_expanded);
+        }
+        Err(error) => println!("{error}"),
+    }
+}
+
+// Whether the path is optimal is checked against Dijkstra, which always finds the cheapest one.
+fn cheapest_cost(grid: &Grid, start: Position, goal: Position) -> Option<i32> {
+    let dijkstra = PathfindingAlgorithm::Dijkstra;
+    find_path(grid, start, goal, &dijkstra, &PathOptions::default())
+        .ok()
+        .map(|cheapest| cheapest.cost)
+}
+
+fn print_optimality(cost: i32, cheapest_cost: Option<i32>) {
+    match cheapest_cost {
+        Some(cheapest_cost) if cost <= cheapest_cost => println!("Optimal: Yes"),
+        Some(cheapest_cost) => println!("Optimal: No, the cheapest path costs {cheapest_cost}"),
+        None => {}
+    }
+}
+
+fn check_optimality(
+    result: &Result<PathResult, PathError>,
+    grid: Arc<Grid>,
+) -> Option<OptimalityCheck> {
+    let result = result.as_ref().ok()?;
+    let (&start, &goal) = (result.path.first()?, result.path.last()?);
+    Some(OptimalityCheck {
+        cost: result.cost,
+        cheapest_cost: AsyncComputeTaskPool::get()
+            .spawn(async move { cheapest_cost(&grid, start, goal) }),
+    })
+}
+
+// Prints whether a path found on the main thread is optimal once its check is done.
+pub fn poll_optimality_check_system(mut solver: ResMut<Solver>) {
+    // Checked through `Deref` first, so that the solver is only marked as changed while checking.
+    if solver.optimality_check.is_none() {
+        return;
+    }
+    let Some(check) = solver.optimality_check.as_mut() else {
+        return;
+    };
+    let Some(cheapest_cost) = future::block_on(future::poll_once(&mut check.cheapest_cost)) else {
+        return;
+    };
+    print_optimality(check.cost, cheapest_cost);
+    solver.optimality_check = None;
 }
 
 // Reset the Path Solve
@@ -261,9 +840,16 @@ pub fn reset_system(
     mut reset_event_reader: EventReader<ResetEvent>,
     mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mut game_state: ResMut<GameState>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
 ) {
     for _ in reset_event_reader.iter() {
         game_state.path = Vec::new();
+        game_state.search = None;
+        game_state.trace = None;
+        game_state.flow_field = None;
+        replanner.d_star_lite = None;
+        solver.pending = None;
         map_updated_event_writer.send(MapUpdatedEvent {});
     }
 }
@@ -273,14 +859,23 @@ pub fn clear_system(
     mut clear_event_reader: EventReader<ClearEvent>,
     mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
     mut game_state: ResMut<GameState>,
-    mut map: ResMut<Map>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
+    mut map: ResMut<Grid>,
 ) {
     for _ in clear_event_reader.iter() {
         game_state.path = Vec::new();
-        game_state.start = Position(16, 32);
-        game_state.goal = Position(48, 32);
-        map.costs = vec![Some(1); (map.width * map.height) as usize];
-        map.blocked = vec![false; (map.width * map.height) as usize];
+        game_state.search = None;
+        game_state.trace = None;
+        game_state.flow_field = None;
+        replanner.d_star_lite = None;
+        solver.pending = None;
+        game_state.start = default_start(&map);
+        game_state.goal = default_goal(&map);
+        map.costs.fill(Some(1));
+        map.blocked.fill(false);
+        map.terrain.fill(TerrainId::FLOOR);
+        map.cost_overrides.fill(None);
         map_updated_event_writer.send(MapUpdatedEvent {});
     }
 }
@@ -292,31 +887,21 @@ pub fn change_pathfinding_algorithm_system(
     mut pathfinding_algorithm_changed_event_writer: EventWriter<PathfindingAlgorithmChangedEvent>,
     mut reset_event_writer: EventWriter<ResetEvent>,
     mut game_state: ResMut<GameState>,
+    mut settings: ResMut<PathfindingSettings>,
 ) {
     for pathfinding_algorithm_selection_changed_event in
         pathfinding_algorithm_selection_changed_event_reader.iter()
     {
-        match pathfinding_algorithm_selection_changed_event.pathfinding_algorithm {
-            PathfindingAlgorithm::AStar => {
-                game_state.pathfinding_algorithm = PathfindingAlgorithm::AStar;
-            }
-            PathfindingAlgorithm::BFS => {
-                game_state.pathfinding_algorithm = PathfindingAlgorithm::BFS;
-            }
-            PathfindingAlgorithm::Dijkstra => {
-                game_state.pathfinding_algorithm = PathfindingAlgorithm::Dijkstra;
-            }
-        }
+        game_state.pathfinding_algorithm =
+            pathfinding_algorithm_selection_changed_event.pathfinding_algorithm;
+        // Path requests from other systems are answered with the selected algorithm as well.
+        settings.pathfinding_algorithm = game_state.pathfinding_algorithm;
         pathfinding_algorithm_changed_event_writer.send(PathfindingAlgorithmChangedEvent {});
         reset_event_writer.send(ResetEvent {});
     }
 }
 
 // References
-// 1. Pathfinding Docs
-// https://docs.rs/pathfinding/latest/pathfinding/directed/astar/fn.astar.html
-// https://docs.rs/pathfinding/latest/pathfinding/directed/bfs/index.html
-// https://docs.rs/pathfinding/latest/pathfinding/directed/dijkstra/index.html
-// 2. Pathfinding in Rust: A tutorial with examples
+// 1. Pathfinding in Rust: A tutorial with examples
 // https://blog.logrocket.com/pathfinding-rust-tutorial-examples/
 // https://github.com/gregstoll/rust-pathfinding
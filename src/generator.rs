@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+
+use super::{
+    add_costs_near_walls, bsp_rooms, cellular_automata, drunkards_walk, eller, kruskal,
+    noise_terrain, prim, recursive_backtracker, wilson, GameState, Grid, MapUpdatedEvent,
+    NoiseKind, NoiseSettings, Position, Replanner, Solver, TerrainRegistry,
+};
+
+// The cost of open tiles right next to a wall when wall costs are turned on.
+const WALL_COST: i32 = 3;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MapGenerator {
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    Eller,
+    CellularAutomata,
+    BspRooms,
+    DrunkardsWalk,
+    ValueNoise,
+    PerlinNoise,
+    SimplexNoise,
+}
+
+impl MapGenerator {
+    pub const ALL: [MapGenerator; 11] = [
+        MapGenerator::RecursiveBacktracker,
+        MapGenerator::Prim,
+        MapGenerator::Kruskal,
+        MapGenerator::Wilson,
+        MapGenerator::Eller,
+        MapGenerator::CellularAutomata,
+        MapGenerator::BspRooms,
+        MapGenerator::DrunkardsWalk,
+        MapGenerator::ValueNoise,
+        MapGenerator::PerlinNoise,
+        MapGenerator::SimplexNoise,
+    ];
+
+    // The generator `offset` places further along `ALL`, wrapping around at either end.
+    #[must_use]
+    pub fn cycle(self, offset: i32) -> MapGenerator {
+        let count = i32::try_from(MapGenerator::ALL.len()).unwrap_or(i32::MAX);
+        let index = MapGenerator::ALL
+            .iter()
+            .position(|generator| *generator == self)
+            .and_then(|index| i32::try_from(index).ok())
+            .unwrap_or(0);
+        MapGenerator::ALL[usize::try_from((index + offset).rem_euclid(count)).unwrap_or_default()]
+    }
+
+    /// Fills the map with this generator, the same seed always gives the same map. Only the noise
+    /// generators set costs on the tiles, all others leave the costs to the terrain.
+    pub fn generate(
+        self,
+        map: &mut Grid,
+        seed: u64,
+        noise_settings: &NoiseSettings,
+        terrains: &TerrainRegistry,
+    ) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        map.cost_overrides.fill(None);
+        match self {
+            MapGenerator::RecursiveBacktracker => recursive_backtracker(map, &mut rng),
+            MapGenerator::Prim => prim(map, &mut rng),
+            MapGenerator::Kruskal => kruskal(map, &mut rng),
+            MapGenerator::Wilson => wilson(map, &mut rng),
+            MapGenerator::Eller => eller(map, &mut rng),
+            MapGenerator::CellularAutomata => cellular_automata(map, &mut rng),
+            MapGenerator::BspRooms => bsp_rooms(map, &mut rng),
+            MapGenerator::DrunkardsWalk => drunkards_walk(map, &mut rng),
+            MapGenerator::ValueNoise => {
+                noise_terrain(map, &mut rng, NoiseKind::Value, noise_settings, terrains);
+            }
+            MapGenerator::PerlinNoise => {
+                noise_terrain(map, &mut rng, NoiseKind::Perlin, noise_settings, terrains);
+            }
+            MapGenerator::SimplexNoise => {
+                noise_terrain(map, &mut rng, NoiseKind::Simplex, noise_settings, terrains);
+            }
+        }
+        // The noise generators paint their own water, all others only place walls.
+        if !matches!(
+            self,
+            MapGenerator::ValueNoise | MapGenerator::PerlinNoise | MapGenerator::SimplexNoise
+        ) {
+            map.reset_terrain();
+        }
+        map.refresh_costs(terrains);
+    }
+}
+
+impl fmt::Display for MapGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MapGenerator::RecursiveBacktracker => "Recursive Backtracker",
+            MapGenerator::Prim => "Prim's Maze",
+            MapGenerator::Kruskal => "Kruskal's Maze",
+            MapGenerator::Wilson => "Wilson's Maze",
+            MapGenerator::Eller => "Eller's Maze",
+            MapGenerator::CellularAutomata => "Cellular Automata Cave",
+            MapGenerator::BspRooms => "BSP Dungeon",
+            MapGenerator::DrunkardsWalk => "Drunkard's Walk Cave",
+            MapGenerator::ValueNoise => "Value Noise Terrain",
+            MapGenerator::PerlinNoise => "Perlin Noise Terrain",
+            MapGenerator::SimplexNoise => "Simplex Noise Terrain",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// === Resources ===
+// The selected generator, and the seed of the next map it generates.
+pub struct MapGeneration {
+    pub generator: MapGenerator,
+    pub seed: u64,
+    // Makes open tiles near walls cost more, so paths keep away from them where they can.
+    pub costs_near_walls: bool,
+    // Read from `config.ron`.
+    pub noise_settings: NoiseSettings,
+}
+
+impl Default for MapGeneration {
+    fn default() -> Self {
+        MapGeneration {
+            generator: MapGenerator::RecursiveBacktracker,
+            seed: 0,
+            costs_near_walls: false,
+            noise_settings: NoiseSettings::default(),
+        }
+    }
+}
+
+/// === Events ===
+pub struct GenerateMapEvent {}
+
+pub struct CycleGeneratorLeftEvent {}
+
+pub struct CycleGeneratorRightEvent {}
+
+pub struct ToggleWallCostsEvent {}
+
+/// === Systems ===
+pub fn cycle_generator_selection_system(
+    mut cycle_generator_left_event_reader: EventReader<CycleGeneratorLeftEvent>,
+    mut cycle_generator_right_event_reader: EventReader<CycleGeneratorRightEvent>,
+    mut map_generation: ResMut<MapGeneration>,
+) {
+    for _ in cycle_generator_left_event_reader.iter() {
+        map_generation.generator = map_generation.generator.cycle(-1);
+    }
+    for _ in cycle_generator_right_event_reader.iter() {
+        map_generation.generator = map_generation.generator.cycle(1);
+    }
+}
+
+pub fn toggle_wall_costs_system(
+    mut toggle_wall_costs_event_reader: EventReader<ToggleWallCostsEvent>,
+    mut map_generation: ResMut<MapGeneration>,
+) {
+    for _ in toggle_wall_costs_event_reader.iter() {
+        map_generation.costs_near_walls = !map_generation.costs_near_walls;
+    }
+}
+
+// Replaces the obstacles and costs with a new map, and moves the start and goal onto the nearest open
+// tiles. Every click uses the next seed, so the same sequence of maps can be generated again.
+pub fn generate_map_system(
+    mut generate_map_event_reader: EventReader<GenerateMapEvent>,
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut map_generation: ResMut<MapGeneration>,
+    mut game_state: ResMut<GameState>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
+    terrains: Res<TerrainRegistry>,
+    mut map: ResMut<Grid>,
+) {
+    if generate_map_event_reader.iter().last().is_none() {
+        return;
+    }
+    let generator = map_generation.generator;
+    let seed = map_generation.seed;
+    println!("Generating a {generator} with seed {seed}...");
+    generator.generate(&mut map, seed, &map_generation.noise_settings, &terrains);
+    if map_generation.costs_near_walls {
+        add_costs_near_walls(&mut map, WALL_COST);
+    }
+    map_generation.seed += 1;
+    if let Some(start) = nearest_open_tile(&map, game_state.start, None) {
+        game_state.start = start;
+    }
+    if let Some(goal) = nearest_open_tile(&map, game_state.goal, Some(game_state.start)) {
+        game_state.goal = goal;
+    }
+    game_state.path = Vec::new();
+    game_state.search = None;
+    game_state.trace = None;
+    game_state.flow_field = None;
+    replanner.d_star_lite = None;
+    solver.pending = None;
+    map_updated_event_writer.send(MapUpdatedEvent {});
+}
+
+/// The open tile closest to `position`, other than `excluded`.
+#[must_use]
+pub fn nearest_open_tile(
+    map: &Grid,
+    position: Position,
+    excluded: Option<Position>,
+) -> Option<Position> {
+    (0..map.height)
+        .flat_map(|y| (0..map.width).map(move |x| Position(x, y)))
+        .filter(|tile| map.is_walkable(tile.0, tile.1) && Some(*tile) != excluded)
+        .min_by_key(|tile| {
+            let dx = tile.0 - position.0;
+            let dy = tile.1 - position.1;
+            dx * dx + dy * dy
+        })
+}
@@ -1,10 +1,19 @@
 #![warn(clippy::all, clippy::pedantic)]
+#![allow(
+    clippy::needless_pass_by_value,
+    reason = "Bevy systems take their resources by value"
+)]
 
 mod camera;
+mod dungeon;
 mod game;
+mod generator;
 mod map;
+mod maze;
 mod mouse;
+mod noise;
 mod physics;
+mod save;
 mod tilemap;
 mod user_interface;
 
@@ -16,22 +25,30 @@ use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_rapier2d::prelude::*;
 
 pub use camera::*;
+pub use dungeon::*;
 pub use game::*;
+pub use generator::*;
+pub use grid_pathfinding::*;
 pub use map::*;
+pub use maze::*;
 pub use mouse::*;
+pub use noise::*;
 pub use physics::*;
+pub use save::*;
 pub use tilemap::*;
 pub use user_interface::*;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
 enum Setup {
     Game,
-    Map,
     TileMap,
     CostsTileMap,
+    FlowFieldTileMap,
+    HeatmapTileMap,
 }
 
 fn main() {
+    let map_config = MapConfig::load();
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
         .insert_resource(WindowDescriptor {
@@ -42,14 +59,26 @@ fn main() {
             ..default()
         })
         .insert_resource(ImageSettings::default_nearest()) // prevents blurry sprites
+        .insert_resource(map_config)
+        .insert_resource(MapGeneration {
+            noise_settings: map_config.noise,
+            ..default()
+        })
+        .init_resource::<TerrainRegistry>()
+        .init_resource::<MovementProfiles>()
         .add_plugins(DefaultPlugins)
         .add_plugin(WorldInspectorPlugin::new()) // bevy_inspector_egui
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(
-            TILE_SIZE as f32,
+            map_config.tile_size as f32,
         )) // bevy_rapier2d, see https://rapier.rs/docs/user_guides/bevy_plugin/common_mistakes#why-is-everything-moving-in-slow-motion
         // .add_plugin(RapierDebugRenderPlugin::default())// bevy_rapier2d debugger
         .add_plugin(TilemapPlugin) // bevy_ecs_tilemap
-        .add_event::<MapUpdatedEvent>()
+        .insert_resource(PathfindingSettings {
+            width: map_config.map_width,
+            height: map_config.map_height,
+            ..default()
+        })
+        .add_plugin(PathfindingPlugin)
         .add_event::<UserInterfaceInteractionEvent>()
         .add_event::<StepEvent>()
         .add_event::<SolveEvent>()
@@ -57,30 +86,74 @@ fn main() {
         .add_event::<ClearEvent>()
         .add_event::<CycleAlgorithmLeftEvent>()
         .add_event::<CycleAlgorithmRightEvent>()
+        .add_event::<CycleTerrainLeftEvent>()
+        .add_event::<CycleTerrainRightEvent>()
+        .add_event::<CycleProfileLeftEvent>()
+        .add_event::<CycleProfileRightEvent>()
+        .add_event::<CycleDiagonalPolicyEvent>()
+        .add_event::<CycleHeuristicLeftEvent>()
+        .add_event::<CycleHeuristicRightEvent>()
+        .add_event::<DecreaseWeightEvent>()
+        .add_event::<IncreaseWeightEvent>()
+        .add_event::<CycleHeatmapModeEvent>()
         .add_event::<PathfindingAlgorithmSelectionChangedEvent>()
         .add_event::<PathfindingAlgorithmChangedEvent>()
+        .add_event::<ResizeMapEvent>()
+        .add_event::<SaveMapEvent>()
+        .add_event::<LoadMapEvent>()
+        .add_event::<GenerateMapEvent>()
+        .add_event::<CycleGeneratorLeftEvent>()
+        .add_event::<CycleGeneratorRightEvent>()
+        .add_event::<ToggleWallCostsEvent>()
         .add_startup_system(setup_physics)
-        .add_startup_system(setup_map.label(Setup::Map))
         .add_startup_system(setup_path_tilemap.label(Setup::TileMap))
         .add_startup_system(setup_costs_tilemap.label(Setup::CostsTileMap))
+        .add_startup_system(setup_flow_field_tilemap.label(Setup::FlowFieldTileMap))
+        .add_startup_system(setup_heatmap_tilemap.label(Setup::HeatmapTileMap))
         .add_startup_system(setup_mouse)
         .add_startup_system(
             setup_game
                 .label(Setup::Game)
-                .after(Setup::Map)
                 .after(Setup::TileMap)
-                .after(Setup::CostsTileMap),
+                .after(Setup::CostsTileMap)
+                .after(Setup::FlowFieldTileMap)
+                .after(Setup::HeatmapTileMap),
         )
         .add_startup_system(setup_camera)
         .add_startup_system(setup_user_interface)
+        // The tilemaps are rebuilt before the systems that draw on them run.
+        .add_system_to_stage(CoreStage::PreUpdate, resize_map_system)
+        .add_system_to_stage(CoreStage::PreUpdate, load_map_system)
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            rebuild_tilemaps_system
+                .after(resize_map_system)
+                .after(load_map_system),
+        )
+        .add_system(resize_map_keyboard_system)
+        .add_system(save_load_keyboard_system)
+        .add_system(save_map_system)
         .add_system(draw_path_tilemap)
+        .add_system(draw_path_segments)
+        .add_system(draw_flow_field_tilemap)
+        .add_system(draw_heatmap_tilemap)
         .add_system(update_cost_tilemap)
         .add_system(placement_system)
         .add_system(cost_system)
         .add_system(step_system)
         .add_system(solve_system)
+        .add_system(poll_solve_system)
+        .add_system(poll_optimality_check_system)
+        .add_system(replan_system)
+        .add_system(replan_comparison_keyboard_system)
+        .add_system(update_profile_paths_system)
+        .add_system(cycle_diagonal_policy_system)
+        .add_system(cycle_heatmap_mode_system)
         .add_system(reset_system)
         .add_system(clear_system)
+        .add_system(generate_map_system)
+        .add_system(cycle_generator_selection_system)
+        .add_system(toggle_wall_costs_system)
         .add_system(camera_movement_system)
         .add_system(update_mouse_position)
         .add_system(update_mouse_input)
@@ -91,16 +164,46 @@ fn main() {
         .add_system(goal_button_system)
         .add_system(increase_cost_button_system)
         .add_system(decrease_cost_button_system)
+        .add_system(terrain_button_system)
+        .add_system(cycle_terrain_left_button_system)
+        .add_system(cycle_terrain_right_button_system)
+        .add_system(cycle_terrain_selection_system)
+        .add_system(update_terrain_button_text_system)
         .add_system(show_hide_increase_decrease_cost_buttons)
         .add_system(step_button_system)
         .add_system(solve_button_system)
         .add_system(reset_button_system)
         .add_system(clear_button_system)
+        .add_system(save_button_system)
+        .add_system(load_button_system)
+        .add_system(generate_button_system)
+        .add_system(cycle_generator_left_button_system)
+        .add_system(cycle_generator_right_button_system)
+        .add_system(update_current_generator_text_system)
+        .add_system(wall_costs_button_system)
+        .add_system(update_wall_costs_button_text_system)
+        .add_system(diagonal_policy_button_system)
+        .add_system(update_diagonal_policy_button_text_system)
+        .add_system(heatmap_mode_button_system)
+        .add_system(update_heatmap_mode_button_text_system)
         .add_system(change_pathfinding_algorithm_system)
         .add_system(cycle_algorithm_left_button_system)
         .add_system(cycle_algorithm_right_button_system)
         .add_system(cycle_algorithm_selection_system)
         .add_system(update_current_algorithm_text_system)
+        .add_system(cycle_heuristic_left_button_system)
+        .add_system(cycle_heuristic_right_button_system)
+        .add_system(cycle_heuristic_selection_system)
+        .add_system(update_current_heuristic_text_system)
+        .add_system(decrease_weight_button_system)
+        .add_system(increase_weight_button_system)
+        .add_system(change_weight_system)
+        .add_system(update_current_weight_text_system)
+        .add_system(cycle_profile_left_button_system)
+        .add_system(cycle_profile_right_button_system)
+        .add_system(cycle_profile_selection_system)
+        .add_system(update_current_profile_text_system)
+        .add_system(update_solve_button_text_system)
         .add_system(show_hide_cost_tilemap)
         .add_system(send_ui_interaction_events_system)
         .run();
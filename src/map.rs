@@ -1,99 +1,129 @@
 use bevy::prelude::*;
+use serde::Deserialize;
+use std::fs;
 
-pub const MAP_WIDTH: i32 = 64;
-pub const MAP_HEIGHT: i32 = 64;
+use super::{
+    nearest_open_tile, GameState, Grid, MapUpdatedEvent, NoiseSettings, Replanner, Solver,
+};
 
-/// === Events ===
-pub struct MapUpdatedEvent {}
+const CONFIG_PATH: &str = "config.ron";
+const MIN_MAP_SIZE: i32 = 8;
+const RESIZE_STEP: i32 = 8;
 
 /// === Resources ===
-#[derive(Debug)]
-pub struct Map {
-    pub width: i32,
-    pub height: i32,
-    pub costs: Vec<Option<i32>>,
-    pub blocked: Vec<bool>,
-    pub allow_diagonals: bool,
+// The map and tile size the app starts with, read from `config.ron` and then the command line,
+// e.g. `cargo run -- --map-width 128 --map-height 96 --tile-size 16`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct MapConfig {
+    pub map_width: i32,
+    pub map_height: i32,
+    pub tile_size: i32,
+    // Used by the noise terrain generators.
+    pub noise: NoiseSettings,
 }
 
-impl Map {
-    pub fn new(width: i32, height: i32, allow_diagonals: bool) -> Map {
-        Map {
-            width,
-            height,
-            costs: vec![Some(1); (width * height) as usize],
-            blocked: vec![false; (width * height) as usize],
-            allow_diagonals,
+impl Default for MapConfig {
+    fn default() -> Self {
+        MapConfig {
+            map_width: 64,
+            map_height: 64,
+            tile_size: 32,
+            noise: NoiseSettings::default(),
         }
     }
+}
 
-    pub fn xy_idx(&self, x: i32, y: i32) -> usize {
-        (y as usize * self.width as usize) + x as usize
-    }
-
-    pub fn get_successors(&self, position: &Position, allow_diagonals: bool) -> Vec<Successor> {
-        let mut successors = Vec::new();
-
-        for dy in -1..=1 {
-            for dx in -1..=1 {
-                let x = position.0 + dx;
-                let y = position.1 + dy;
-                if dx == 0 && dy == 0 {
-                    continue;
-                } // Exclude current position.
-                if !allow_diagonals {
-                    if (dx + dy).abs() != 1 {
-                        continue;
-                    } // Exclude diagonals.
-                }
-                if x < 0 || x > self.width - 1 {
-                    continue;
-                } // Make sure we are within width bounds.
-                if y < 0 || y > self.height - 1 {
-                    continue;
-                } // Make sure we are within height bounds.
-
-                let neighbor_position = Position(x, y);
-                let neighbor_index = self.xy_idx(x, y);
-                if self.blocked[neighbor_index] {
+impl MapConfig {
+    // Falls back to the defaults for anything that can't be read, after reporting why.
+    #[must_use]
+    pub fn load() -> MapConfig {
+        let mut map_config = match fs::read_to_string(CONFIG_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                println!("Could not read {CONFIG_PATH}: {error}");
+                MapConfig::default()
+            }),
+            Err(_) => MapConfig::default(),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let field = match arg.as_str() {
+                "--map-width" => &mut map_config.map_width,
+                "--map-height" => &mut map_config.map_height,
+                "--tile-size" => &mut map_config.tile_size,
+                _ => {
+                    println!("Unknown argument: {arg}");
                     continue;
                 }
-                let neighbor_cost = self.costs[neighbor_index];
-                if let Some(neighbor_cost) = neighbor_cost {
-                    successors.push(Successor {
-                        position: neighbor_position,
-                        cost: neighbor_cost,
-                    })
-                } else {
-                    successors.push(Successor {
-                        position: neighbor_position,
-                        cost: 1,
-                    })
-                }
+            };
+            match args.next().map(|value| value.parse::<i32>()) {
+                Some(Ok(value)) if value >= 1 => *field = value,
+                _ => println!("{arg} needs a positive number."),
             }
         }
-        successors
+        map_config.map_width = map_config.map_width.max(MIN_MAP_SIZE);
+        map_config.map_height = map_config.map_height.max(MIN_MAP_SIZE);
+        map_config
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct Position(pub i32, pub i32);
-
-impl Position {
-    pub fn distance(&self, other: &Position) -> i32 {
-        (self.0.abs_diff(other.0) + self.1.abs_diff(other.1)) as i32
-    }
+/// === Events ===
+pub struct ResizeMapEvent {
+    pub width: i32,
+    pub height: i32,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd)]
-pub struct Successor {
-    pub position: Position,
-    pub cost: i32,
+/// === Systems ===
+// `]` grows the map and `[` shrinks it.
+pub fn resize_map_keyboard_system(
+    keyboard: Res<Input<KeyCode>>,
+    mut resize_map_event_writer: EventWriter<ResizeMapEvent>,
+    map: Res<Grid>,
+) {
+    let step = if keyboard.just_pressed(KeyCode::RBracket) {
+        RESIZE_STEP
+    } else if keyboard.just_pressed(KeyCode::LBracket) {
+        -RESIZE_STEP
+    } else {
+        return;
+    };
+    resize_map_event_writer.send(ResizeMapEvent {
+        width: map.width + step,
+        height: map.height + step,
+    });
 }
 
-/// === Systems ===
-pub fn setup_map(mut commands: Commands) {
-    println!("Setup Map...");
-    let map = Map::new(MAP_WIDTH, MAP_HEIGHT, false);
-    commands.insert_resource(map);
+// Keeps everything inside both sizes, and moves the start and goal onto the nearest open tiles if
+// they ended up outside of the map or on top of each other. The tilemaps are rebuilt by `rebuild_tilemaps_system`.
+pub fn resize_map_system(
+    mut resize_map_event_reader: EventReader<ResizeMapEvent>,
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut game_state: ResMut<GameState>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
+    mut map: ResMut<Grid>,
+) {
+    let Some(resize_map_event) = resize_map_event_reader.iter().last() else {
+        return;
+    };
+    let width = resize_map_event.width.max(MIN_MAP_SIZE);
+    let height = resize_map_event.height.max(MIN_MAP_SIZE);
+    if width == map.width && height == map.height {
+        return;
+    }
+    println!("Resizing the map to {width}x{height}...");
+    *map = map.resized(width, height);
+    if let Some(start) = nearest_open_tile(&map, game_state.start, None) {
+        game_state.start = start;
+    }
+    if let Some(goal) = nearest_open_tile(&map, game_state.goal, Some(game_state.start)) {
+        game_state.goal = goal;
+    }
+    game_state.path = Vec::new();
+    game_state.search = None;
+    game_state.trace = None;
+    game_state.flow_field = None;
+    replanner.d_star_lite = None;
+    solver.pending = None;
+    map_updated_event_writer.send(MapUpdatedEvent {});
 }
@@ -0,0 +1,260 @@
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::RngExt;
+
+use super::Grid;
+
+// Mazes are carved on a grid of cells at odd tile coordinates, with a wall tile between every
+// pair of neighbouring cells. All of these mazes are perfect: every open tile can reach every
+// other open tile in exactly one way.
+type Cell = (i32, i32);
+
+struct MazeGrid<'a> {
+    map: &'a mut Grid,
+    columns: i32,
+    rows: i32,
+}
+
+impl<'a> MazeGrid<'a> {
+    // Blocks every tile, the cells and passages are opened as they are carved.
+    fn new(map: &'a mut Grid) -> Self {
+        map.blocked.fill(true);
+        let columns = ((map.width - 1) / 2).max(1);
+        let rows = ((map.height - 1) / 2).max(1);
+        MazeGrid { map, columns, rows }
+    }
+
+    fn cell_count(&self) -> usize {
+        usize::try_from(self.columns * self.rows).unwrap_or_default()
+    }
+
+    fn index(&self, cell: Cell) -> usize {
+        usize::try_from(cell.1 * self.columns + cell.0).unwrap_or_default()
+    }
+
+    fn cell(&self, index: usize) -> Cell {
+        let index = i32::try_from(index).unwrap_or_default();
+        (index % self.columns, index / self.columns)
+    }
+
+    // The cell in column `x` of row `y`, for walking over a row by index.
+    fn row_cell(x: usize, y: i32) -> Cell {
+        (i32::try_from(x).unwrap_or_default(), y)
+    }
+
+    fn neighbours(&self, cell: Cell) -> Vec<Cell> {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .map(|(dx, dy)| (cell.0 + dx, cell.1 + dy))
+            .filter(|(x, y)| *x >= 0 && *x < self.columns && *y >= 0 && *y < self.rows)
+            .collect()
+    }
+
+    fn open(&mut self, x: i32, y: i32) {
+        if self.map.in_bounds(x, y) {
+            let index = self.map.xy_idx(x, y);
+            self.map.blocked[index] = false;
+        }
+    }
+
+    fn carve_cell(&mut self, cell: Cell) {
+        self.open(cell.0 * 2 + 1, cell.1 * 2 + 1);
+    }
+
+    // Opens both cells and the wall between them.
+    fn carve_passage(&mut self, from: Cell, to: Cell) {
+        self.carve_cell(from);
+        self.carve_cell(to);
+        self.open(from.0 + to.0 + 1, from.1 + to.1 + 1);
+    }
+
+    fn random_cell(&self, rng: &mut StdRng) -> Cell {
+        self.cell(rng.random_range(0..self.cell_count()))
+    }
+}
+
+// Walks to a random unvisited neighbour, and backs up whenever it gets stuck. This gives long,
+// winding corridors with few dead ends.
+pub fn recursive_backtracker(map: &mut Grid, rng: &mut StdRng) {
+    let mut maze = MazeGrid::new(map);
+    let mut visited = vec![false; maze.cell_count()];
+    let start = maze.random_cell(rng);
+    maze.carve_cell(start);
+    visited[maze.index(start)] = true;
+    let mut stack = vec![start];
+    while let Some(&cell) = stack.last() {
+        let unvisited: Vec<Cell> = maze
+            .neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| !visited[maze.index(*neighbour)])
+            .collect();
+        let Some(&next) = unvisited.choose(rng) else {
+            stack.pop();
+            continue;
+        };
+        maze.carve_passage(cell, next);
+        visited[maze.index(next)] = true;
+        stack.push(next);
+    }
+}
+
+// Grows the maze from a single cell by carving a random wall on its frontier each step, which
+// gives many short dead ends.
+pub fn prim(map: &mut Grid, rng: &mut StdRng) {
+    let mut maze = MazeGrid::new(map);
+    let mut in_maze = vec![false; maze.cell_count()];
+    let start = maze.random_cell(rng);
+    maze.carve_cell(start);
+    in_maze[maze.index(start)] = true;
+    let mut frontier: Vec<(Cell, Cell)> = maze
+        .neighbours(start)
+        .into_iter()
+        .map(|neighbour| (start, neighbour))
+        .collect();
+    while !frontier.is_empty() {
+        let (from, to) = frontier.swap_remove(rng.random_range(0..frontier.len()));
+        if in_maze[maze.index(to)] {
+            continue;
+        }
+        maze.carve_passage(from, to);
+        in_maze[maze.index(to)] = true;
+        for neighbour in maze.neighbours(to) {
+            if !in_maze[maze.index(neighbour)] {
+                frontier.push((to, neighbour));
+            }
+        }
+    }
+}
+
+// Carves the walls in a random order, skipping any wall whose cells are already connected.
+pub fn kruskal(map: &mut Grid, rng: &mut StdRng) {
+    let mut maze = MazeGrid::new(map);
+    let mut walls = Vec::new();
+    for y in 0..maze.rows {
+        for x in 0..maze.columns {
+            if x + 1 < maze.columns {
+                walls.push(((x, y), (x + 1, y)));
+            }
+            if y + 1 < maze.rows {
+                walls.push(((x, y), (x, y + 1)));
+            }
+        }
+    }
+    walls.shuffle(rng);
+    let mut parents: Vec<usize> = (0..maze.cell_count()).collect();
+    for (from, to) in walls {
+        let from_root = find_root(&mut parents, maze.index(from));
+        let to_root = find_root(&mut parents, maze.index(to));
+        if from_root != to_root {
+            parents[to_root] = from_root;
+            maze.carve_passage(from, to);
+        }
+    }
+    // A maze of a single cell has no walls to carve.
+    maze.carve_cell((0, 0));
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+// Adds loop-erased random walks to the maze until every cell is in it. Every possible maze is
+// equally likely.
+pub fn wilson(map: &mut Grid, rng: &mut StdRng) {
+    let mut maze = MazeGrid::new(map);
+    let mut in_maze = vec![false; maze.cell_count()];
+    let first = maze.random_cell(rng);
+    maze.carve_cell(first);
+    in_maze[maze.index(first)] = true;
+    let mut remaining: Vec<usize> = (0..maze.cell_count()).collect();
+    remaining.shuffle(rng);
+    // The direction the walk last left each cell in, so revisiting a cell erases the loop.
+    let mut next = vec![None; maze.cell_count()];
+    for start_index in remaining {
+        if in_maze[start_index] {
+            continue;
+        }
+        let mut cell = maze.cell(start_index);
+        while !in_maze[maze.index(cell)] {
+            // Every cell has a neighbour in a maze of more than one cell.
+            let Some(&neighbour) = maze.neighbours(cell).choose(rng) else {
+                break;
+            };
+            next[maze.index(cell)] = Some(neighbour);
+            cell = neighbour;
+        }
+        let mut cell = maze.cell(start_index);
+        while !in_maze[maze.index(cell)] {
+            let index = maze.index(cell);
+            in_maze[index] = true;
+            // The walk left every cell it visited.
+            let Some(neighbour) = next[index] else {
+                break;
+            };
+            maze.carve_passage(cell, neighbour);
+            cell = neighbour;
+        }
+    }
+}
+
+// Builds the maze one row at a time, only remembering which cells of the current row are
+// connected. Cells in different sets are randomly joined, and every set continues into the next
+// row at least once, except in the last row which joins all remaining sets.
+pub fn eller(map: &mut Grid, rng: &mut StdRng) {
+    let mut maze = MazeGrid::new(map);
+    let columns = usize::try_from(maze.columns).unwrap_or_default();
+    let mut sets = vec![0; columns];
+    let mut next_set = 1;
+    for y in 0..maze.rows {
+        let last_row = y == maze.rows - 1;
+        for (x, set) in sets.iter_mut().enumerate() {
+            if *set == 0 {
+                *set = next_set;
+                next_set += 1;
+            }
+            maze.carve_cell(MazeGrid::row_cell(x, y));
+        }
+        for x in 0..columns.saturating_sub(1) {
+            if sets[x] != sets[x + 1] && (last_row || rng.random_bool(0.5)) {
+                maze.carve_passage(MazeGrid::row_cell(x, y), MazeGrid::row_cell(x + 1, y));
+                let (kept_set, merged_set) = (sets[x], sets[x + 1]);
+                for set in &mut sets {
+                    if *set == merged_set {
+                        *set = kept_set;
+                    }
+                }
+            }
+        }
+        if last_row {
+            break;
+        }
+        let mut next_row_sets = vec![0; columns];
+        let mut unique_sets = sets.clone();
+        unique_sets.sort_unstable();
+        unique_sets.dedup();
+        for set in unique_sets {
+            let members: Vec<usize> = (0..columns).filter(|x| sets[*x] == set).collect();
+            let mut down: Vec<usize> = members
+                .iter()
+                .copied()
+                .filter(|_| rng.random_bool(0.5))
+                .collect();
+            if down.is_empty() {
+                down.extend(members.choose(rng));
+            }
+            for x in down {
+                maze.carve_passage(MazeGrid::row_cell(x, y), MazeGrid::row_cell(x, y + 1));
+                next_row_sets[x] = set;
+            }
+        }
+        sets = next_row_sets;
+    }
+}
+
+// References
+// 1. https://weblog.jamisbuck.org/2011/2/7/maze-generation-algorithm-recap
+// 2. https://en.wikipedia.org/wiki/Maze_generation_algorithm
@@ -0,0 +1,224 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use super::{Grid, Position, TerrainId, TerrainRegistry};
+
+// Every octave has twice the frequency and half the amplitude of the one before.
+const LACUNARITY: f64 = 2.0;
+const PERSISTENCE: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NoiseKind {
+    Value,
+    Perlin,
+    Simplex,
+}
+
+/// How noise is turned into terrain costs, read from the `noise` field of `config.ron`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct NoiseSettings {
+    /// How many layers of noise are added together, more octaves add finer detail.
+    pub octaves: u32,
+    /// The size in tiles of the largest features.
+    pub scale: f64,
+    pub min_cost: i32,
+    pub max_cost: i32,
+    /// Tiles with noise below this, on a scale from 0 to 1, become blocked water. 0 turns it off.
+    pub water_threshold: f64,
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        NoiseSettings {
+            octaves: 4,
+            scale: 16.0,
+            min_cost: 1,
+            max_cost: 9,
+            water_threshold: 0.3,
+        }
+    }
+}
+
+// Fills the costs from layered noise, scaled so the lowest noise on the map costs `min_cost` and
+// the highest `max_cost`. Tiles below the water threshold become water, all others floor, and
+// keep the noise cost as their own.
+pub fn noise_terrain(
+    map: &mut Grid,
+    rng: &mut StdRng,
+    kind: NoiseKind,
+    settings: &NoiseSettings,
+    terrains: &TerrainRegistry,
+) {
+    let permutation = Permutation::new(rng);
+    let octaves = settings.octaves.max(1);
+    let scale = settings.scale.max(1.0);
+    let mut heights = Vec::with_capacity(map.costs.len());
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let mut height = 0.0;
+            let mut amplitude = 1.0;
+            let mut frequency = 1.0 / scale;
+            for _ in 0..octaves {
+                let (nx, ny) = (f64::from(x) * frequency, f64::from(y) * frequency);
+                height += amplitude
+                    * match kind {
+                        NoiseKind::Value => permutation.value(nx, ny),
+                        NoiseKind::Perlin => permutation.perlin(nx, ny),
+                        NoiseKind::Simplex => permutation.simplex(nx, ny),
+                    };
+                amplitude *= PERSISTENCE;
+                frequency *= LACUNARITY;
+            }
+            heights.push(height);
+        }
+    }
+    let lowest = heights.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (highest - lowest).max(f64::EPSILON);
+    let min_cost = settings.min_cost.max(1);
+    let max_cost = settings.max_cost.max(min_cost);
+    let positions: Vec<Position> = (0..map.height)
+        .flat_map(|y| (0..map.width).map(move |x| Position(x, y)))
+        .collect();
+    for (position, height) in positions.into_iter().zip(heights) {
+        let height = (height - lowest) / range;
+        let cost = f64::from(min_cost) + height * f64::from(max_cost - min_cost);
+        let terrain = if height < settings.water_threshold {
+            TerrainId::WATER
+        } else {
+            TerrainId::FLOOR
+        };
+        map.set_terrain(position, terrain, terrains);
+        map.set_cost(position, whole(cost.round()));
+    }
+}
+
+// A shuffled table of 0 to 255, repeated once so lookups of `index + 1` don't need wrapping.
+// This is what makes the noise depend on the seed.
+struct Permutation(Vec<u8>);
+
+impl Permutation {
+    fn new(rng: &mut StdRng) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        table.shuffle(rng);
+        table.extend_from_within(..);
+        Permutation(table)
+    }
+
+    fn hash(&self, x: i32, y: i32) -> u8 {
+        // The lowest byte, which wraps negative coordinates around like `& 255` does.
+        let (x, y) = (x.to_le_bytes()[0], y.to_le_bytes()[0]);
+        self.0[usize::from(self.0[usize::from(x)]) + usize::from(y)]
+    }
+
+    // Random values on the corners of every lattice cell, blended smoothly in between. From 0 to 1.
+    fn value(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (u, v) = (fade(x - x0), fade(y - y0));
+        let (xi, yi) = (whole(x0), whole(y0));
+        let corner = |dx: i32, dy: i32| f64::from(self.hash(xi + dx, yi + dy)) / 255.0;
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        )
+    }
+
+    // Random gradients on the corners of every lattice cell instead of values, which avoids the
+    // blocky look of value noise. Roughly from -1 to 1.
+    fn perlin(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (u, v) = (fade(fx), fade(fy));
+        let (xi, yi) = (whole(x0), whole(y0));
+        let corner = |dx: i32, dy: i32| {
+            gradient(
+                self.hash(xi + dx, yi + dy),
+                fx - f64::from(dx),
+                fy - f64::from(dy),
+            )
+        };
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        )
+    }
+
+    // Gradient noise on a grid of triangles instead of squares, which has fewer directional
+    // artifacts than Perlin noise. Roughly from -1 to 1.
+    fn simplex(&self, x: f64, y: f64) -> f64 {
+        let skew = 0.5 * (3.0_f64.sqrt() - 1.0);
+        let unskew = (3.0 - 3.0_f64.sqrt()) / 6.0;
+        let skewed = (x + y) * skew;
+        let (cell_x, cell_y) = ((x + skewed).floor(), (y + skewed).floor());
+        let unskewed = (cell_x + cell_y) * unskew;
+        let (x0, y0) = (x - (cell_x - unskewed), y - (cell_y - unskewed));
+        // Which of the two triangles of the skewed cell the point is in.
+        let (x1, y1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let corners = [
+            (0, 0, x0, y0),
+            (
+                x1,
+                y1,
+                x0 - f64::from(x1) + unskew,
+                y0 - f64::from(y1) + unskew,
+            ),
+            (1, 1, x0 - 1.0 + 2.0 * unskew, y0 - 1.0 + 2.0 * unskew),
+        ];
+        let (xi, yi) = (whole(cell_x), whole(cell_y));
+        let sum: f64 = corners
+            .iter()
+            .map(|(dx, dy, offset_x, offset_y)| {
+                let falloff = 0.5 - offset_x * offset_x - offset_y * offset_y;
+                if falloff < 0.0 {
+                    0.0
+                } else {
+                    falloff.powi(4) * gradient(self.hash(xi + dx, yi + dy), *offset_x, *offset_y)
+                }
+            })
+            .sum();
+        70.0 * sum
+    }
+}
+
+// Eases from 0 to 1 with a flat start and end, so the noise has no visible cell edges.
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+// Rounds towards zero, for the lattice coordinates and the costs of the tiles.
+#[allow(
+    clippy::cast_possible_truncation,
+    reason = "the fraction is meant to be dropped and `as` saturates instead of wrapping"
+)]
+fn whole(value: f64) -> i32 {
+    value as i32
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + t * (b - a)
+}
+
+// The dot product of the offset with one of 8 gradient directions picked by the hash.
+fn gradient(hash: u8, x: f64, y: f64) -> f64 {
+    match hash & 7 {
+        0 => x + y,
+        1 => x - y,
+        2 => -x + y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+// References
+// 1. Improving Noise, Ken Perlin
+// https://mrl.cs.nyu.edu/~perlin/paper445.pdf
+// 2. Simplex noise demystified, Stefan Gustavson
+// https://weber.itn.liu.se/~stegu/simplexnoise/simplexnoise.pdf
+// 3. https://www.redblobgames.com/maps/terrain-from-noise/
@@ -0,0 +1,464 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+
+use super::{
+    DiagonalPolicy, GameState, Grid, MapUpdatedEvent, Position, Replanner, Solver, TerrainId,
+    TerrainRegistry,
+};
+
+const SAVE_PATH: &str = "saved_map.ron";
+// Bump this whenever the fields of `SavedMap` change.
+const SAVE_VERSION: u32 = 4;
+
+/// === Save File ===
+// Everything needed to restore a drawn layout. Positions are stored as `(x, y)` so the
+// library crate doesn't need serde.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SavedMap {
+    pub version: u32,
+    pub width: i32,
+    pub height: i32,
+    // Replaced by `cost_overrides` in version 4, only read from older maps.
+    #[serde(default, skip_serializing)]
+    pub costs: Vec<Option<i32>>,
+    pub blocked: Vec<bool>,
+    // Added in version 2, version 1 maps get walls and floor from `blocked` instead.
+    #[serde(default)]
+    pub terrain: Vec<usize>,
+    // Replaced by `diagonal_policy` in version 3, only read from older maps.
+    #[serde(default, skip_serializing)]
+    pub allow_diagonals: bool,
+    // Added in version 3, stored by its displayed name.
+    #[serde(default)]
+    pub diagonal_policy: String,
+    // Added in version 4, every other tile takes its cost from its terrain.
+    #[serde(default)]
+    pub cost_overrides: Vec<Option<i32>>,
+    pub start: (i32, i32),
+    pub goal: (i32, i32),
+}
+
+impl SavedMap {
+    #[must_use]
+    pub fn new(map: &Grid, start: Position, goal: Position) -> SavedMap {
+        SavedMap {
+            version: SAVE_VERSION,
+            width: map.width,
+            height: map.height,
+            costs: Vec::new(),
+            blocked: map.blocked.clone(),
+            terrain: map.terrain.iter().map(|terrain| terrain.0).collect(),
+            allow_diagonals: map.allows_diagonals(),
+            diagonal_policy: map.diagonal_policy.to_string(),
+            cost_overrides: map.cost_overrides.clone(),
+            start: (start.0, start.1),
+            goal: (goal.0, goal.1),
+        }
+    }
+
+    /// Checks the data before it's used, so a bad file can't make `xy_idx` panic later on.
+    ///
+    /// # Errors
+    /// Returns an error if the file is from a newer version, the size isn't positive, the tiles
+    /// don't match the size, a cost is below 1, a terrain isn't in the registry, the diagonal
+    /// policy is unknown, or the start or goal is outside of the map.
+    pub fn into_map(
+        self,
+        terrains: &TerrainRegistry,
+    ) -> Result<(Grid, Position, Position), SaveFileError> {
+        if self.version > SAVE_VERSION {
+            return Err(SaveFileError::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        if self.width < 1 || self.height < 1 {
+            return Err(SaveFileError::InvalidSize {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        let expected = usize::try_from(self.width).unwrap_or_default()
+            * usize::try_from(self.height).unwrap_or_default();
+        // Older maps stored the cost of every tile instead of only the overrides.
+        let (costs_field, costs) = if self.version < 4 {
+            ("costs", self.costs)
+        } else {
+            ("cost_overrides", self.cost_overrides)
+        };
+        let mut fields = vec![(costs_field, costs.len()), ("blocked", self.blocked.len())];
+        if self.version >= 2 {
+            fields.push(("terrain", self.terrain.len()));
+        }
+        for (field, found) in fields {
+            if found != expected {
+                return Err(SaveFileError::LengthMismatch {
+                    field,
+                    expected,
+                    found,
+                });
+            }
+        }
+        if let Some(cost) = costs.iter().flatten().find(|cost| **cost < 1) {
+            return Err(SaveFileError::InvalidCost { cost: *cost });
+        }
+        if let Some(id) = self
+            .terrain
+            .iter()
+            .find(|id| terrains.get(TerrainId(**id)).is_none())
+        {
+            return Err(SaveFileError::UnknownTerrain { id: *id });
+        }
+        let diagonal_policy = if self.version < 3 {
+            if self.allow_diagonals {
+                DiagonalPolicy::Always
+            } else {
+                DiagonalPolicy::Never
+            }
+        } else {
+            DiagonalPolicy::ALL
+                .into_iter()
+                .find(|policy| policy.to_string() == self.diagonal_policy)
+                .ok_or(SaveFileError::UnknownDiagonalPolicy {
+                    name: self.diagonal_policy,
+                })?
+        };
+        let mut map = Grid {
+            width: self.width,
+            height: self.height,
+            costs: vec![Some(1); expected],
+            blocked: self.blocked,
+            terrain: self.terrain.into_iter().map(TerrainId).collect(),
+            cost_overrides: vec![None; expected],
+            diagonal_policy,
+        };
+        if self.version < 2 {
+            map.terrain = vec![TerrainId::FLOOR; expected];
+            map.reset_terrain();
+        }
+        if self.version < 4 {
+            // Only the costs that differ from the terrain are kept.
+            for (index, cost) in costs.into_iter().enumerate() {
+                let terrain_cost = terrains.get(map.terrain[index]).map(|terrain| terrain.cost);
+                if cost.is_some() && cost != terrain_cost {
+                    map.cost_overrides[index] = cost;
+                }
+            }
+        } else {
+            map.cost_overrides = costs;
+        }
+        map.refresh_costs(terrains);
+        let start = Position(self.start.0, self.start.1);
+        let goal = Position(self.goal.0, self.goal.1);
+        for (name, position) in [("start", start), ("goal", goal)] {
+            if !map.in_bounds(position.0, position.1) {
+                return Err(SaveFileError::OutOfBounds { name, position });
+            }
+        }
+        Ok((map, start, goal))
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveFileError {
+    Io(io::Error),
+    Format(String),
+    UnsupportedVersion {
+        version: u32,
+    },
+    InvalidSize {
+        width: i32,
+        height: i32,
+    },
+    LengthMismatch {
+        field: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCost {
+        cost: i32,
+    },
+    UnknownTerrain {
+        id: usize,
+    },
+    UnknownDiagonalPolicy {
+        name: String,
+    },
+    OutOfBounds {
+        name: &'static str,
+        position: Position,
+    },
+}
+
+impl Display for SaveFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveFileError::Io(error) => write!(f, "{error}"),
+            SaveFileError::Format(error) => write!(f, "Invalid save file: {error}"),
+            SaveFileError::UnsupportedVersion { version } => write!(
+                f,
+                "Save file version {version} is newer than the supported version {SAVE_VERSION}."
+            ),
+            SaveFileError::InvalidSize { width, height } => {
+                write!(f, "A map of {width}x{height} tiles has no tiles.")
+            }
+            SaveFileError::LengthMismatch {
+                field,
+                expected,
+                found,
+            } => write!(f, "Expected {expected} {field} entries, found {found}."),
+            SaveFileError::InvalidCost { cost } => {
+                write!(f, "Tile costs must be at least 1, found {cost}.")
+            }
+            SaveFileError::UnknownTerrain { id } => {
+                write!(f, "There is no terrain with id {id}.")
+            }
+            SaveFileError::UnknownDiagonalPolicy { name } => {
+                write!(f, "There is no diagonal policy called {name:?}.")
+            }
+            SaveFileError::OutOfBounds { name, position } => {
+                write!(f, "The {name} {position:?} is outside of the map.")
+            }
+        }
+    }
+}
+
+impl Error for SaveFileError {}
+
+impl From<io::Error> for SaveFileError {
+    fn from(error: io::Error) -> Self {
+        SaveFileError::Io(error)
+    }
+}
+
+/// Writes the map, start and goal to a RON file.
+///
+/// # Errors
+/// Returns an error if the file can't be written.
+pub fn save_map(
+    path: &str,
+    map: &Grid,
+    start: Position,
+    goal: Position,
+) -> Result<(), SaveFileError> {
+    // One line per field instead of one per tile.
+    let pretty_config = PrettyConfig::new().compact_arrays(true);
+    let contents = ron::ser::to_string_pretty(&SavedMap::new(map, start, goal), pretty_config)
+        .map_err(|error| SaveFileError::Format(error.to_string()))?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Reads a map, start and goal from a RON file written by `save_map`.
+///
+/// # Errors
+/// Returns an error if the file can't be read or doesn't hold a valid map.
+pub fn load_map(
+    path: &str,
+    terrains: &TerrainRegistry,
+) -> Result<(Grid, Position, Position), SaveFileError> {
+    let contents = fs::read_to_string(path)?;
+    let saved_map: SavedMap =
+        ron::from_str(&contents).map_err(|error| SaveFileError::Format(error.to_string()))?;
+    saved_map.into_map(terrains)
+}
+
+/// === Events ===
+pub struct SaveMapEvent {}
+
+pub struct LoadMapEvent {}
+
+/// === Systems ===
+// F5 saves the map and F9 loads it again.
+pub fn save_load_keyboard_system(
+    keyboard: Res<Input<KeyCode>>,
+    mut save_map_event_writer: EventWriter<SaveMapEvent>,
+    mut load_map_event_writer: EventWriter<LoadMapEvent>,
+) {
+    if keyboard.just_pressed(KeyCode::F5) {
+        save_map_event_writer.send(SaveMapEvent {});
+    }
+    if keyboard.just_pressed(KeyCode::F9) {
+        load_map_event_writer.send(LoadMapEvent {});
+    }
+}
+
+pub fn save_map_system(
+    mut save_map_event_reader: EventReader<SaveMapEvent>,
+    game_state: Res<GameState>,
+    map: Res<Grid>,
+) {
+    if save_map_event_reader.iter().last().is_none() {
+        return;
+    }
+    match save_map(SAVE_PATH, &map, game_state.start, game_state.goal) {
+        Ok(()) => println!("Saved the map to {SAVE_PATH}."),
+        Err(error) => println!("Could not save the map: {error}"),
+    }
+}
+
+// The current map is kept when the file can't be loaded. A map of another size is picked up by
+// `rebuild_tilemaps_system`.
+pub fn load_map_system(
+    mut load_map_event_reader: EventReader<LoadMapEvent>,
+    mut map_updated_event_writer: EventWriter<MapUpdatedEvent>,
+    mut game_state: ResMut<GameState>,
+    mut replanner: ResMut<Replanner>,
+    mut solver: ResMut<Solver>,
+    terrains: Res<TerrainRegistry>,
+    mut map: ResMut<Grid>,
+) {
+    if load_map_event_reader.iter().last().is_none() {
+        return;
+    }
+    let (loaded_map, start, goal) = match load_map(SAVE_PATH, &terrains) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            println!("Could not load {SAVE_PATH}: {error}");
+            return;
+        }
+    };
+    println!("Loaded the map from {SAVE_PATH}.");
+    *map = loaded_map;
+    game_state.start = start;
+    game_state.goal = goal;
+    game_state.path = Vec::new();
+    game_state.search = None;
+    game_state.trace = None;
+    game_state.flow_field = None;
+    replanner.d_star_lite = None;
+    solver.pending = None;
+    map_updated_event_writer.send(MapUpdatedEvent {});
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> Grid {
+        let mut map = Grid::new(3, 2, DiagonalPolicy::NoCornerCutting);
+        map.set_terrain(Position(1, 0), TerrainId::WALL, &TerrainRegistry::default());
+        map.set_cost(Position(2, 1), 5);
+        map
+    }
+
+    fn saved_map() -> SavedMap {
+        SavedMap::new(&map(), Position(0, 0), Position(2, 1))
+    }
+
+    fn error(saved_map: SavedMap) -> SaveFileError {
+        saved_map
+            .into_map(&TerrainRegistry::default())
+            .expect_err("the map should be rejected")
+    }
+
+    #[test]
+    fn saved_map_round_trips() {
+        let contents = ron::to_string(&saved_map()).expect("the map should serialize");
+        let saved_map: SavedMap = ron::from_str(&contents).expect("the map should deserialize");
+        let (loaded, start, goal) = saved_map
+            .into_map(&TerrainRegistry::default())
+            .expect("the map should be valid");
+        assert_eq!(loaded, map());
+        assert_eq!((start, goal), (Position(0, 0), Position(2, 1)));
+    }
+
+    #[test]
+    fn version_1_maps_get_terrain_from_blocked() {
+        let contents = "(version: 1, width: 2, height: 1, costs: [Some(1), Some(1)], \
+            blocked: [true, false], allow_diagonals: true, start: (1, 0), goal: (1, 0))";
+        let saved_map: SavedMap = ron::from_str(contents).expect("the map should deserialize");
+        let (map, _, _) = saved_map
+            .into_map(&TerrainRegistry::default())
+            .expect("the map should be valid");
+        assert_eq!(map.terrain, vec![TerrainId::WALL, TerrainId::FLOOR]);
+        assert_eq!(map.diagonal_policy, DiagonalPolicy::Always);
+    }
+
+    #[test]
+    fn version_3_costs_become_overrides() {
+        let contents = "(version: 3, width: 3, height: 1, costs: [Some(4), Some(6), Some(1)], \
+            blocked: [false, false, false], terrain: [4, 4, 0], diagonal_policy: \"Never\", \
+            start: (0, 0), goal: (2, 0))";
+        let saved_map: SavedMap = ron::from_str(contents).expect("the map should deserialize");
+        let (map, _, _) = saved_map
+            .into_map(&TerrainRegistry::default())
+            .expect("the map should be valid");
+        assert_eq!(map.cost_overrides, vec![None, Some(6), None]);
+        assert_eq!(map.costs, vec![Some(4), Some(6), Some(1)]);
+    }
+
+    #[test]
+    fn invalid_maps_are_rejected() {
+        let mut newer = saved_map();
+        newer.version = SAVE_VERSION + 1;
+        assert!(matches!(
+            error(newer),
+            SaveFileError::UnsupportedVersion { .. }
+        ));
+
+        let mut empty = saved_map();
+        empty.height = 0;
+        assert!(matches!(
+            error(empty),
+            SaveFileError::InvalidSize {
+                width: 3,
+                height: 0
+            }
+        ));
+
+        let mut short = saved_map();
+        short.cost_overrides.pop();
+        assert_eq!(
+            error(short).to_string(),
+            "Expected 6 cost_overrides entries, found 5."
+        );
+
+        let mut short_terrain = saved_map();
+        short_terrain.terrain.push(0);
+        assert!(matches!(
+            error(short_terrain),
+            SaveFileError::LengthMismatch {
+                field: "terrain",
+                expected: 6,
+                found: 7
+            }
+        ));
+
+        let mut free = saved_map();
+        free.cost_overrides[0] = Some(0);
+        assert!(matches!(
+            error(free),
+            SaveFileError::InvalidCost { cost: 0 }
+        ));
+
+        let mut unknown_terrain = saved_map();
+        unknown_terrain.terrain[0] = 99;
+        assert!(matches!(
+            error(unknown_terrain),
+            SaveFileError::UnknownTerrain { id: 99 }
+        ));
+
+        let mut unknown_policy = saved_map();
+        unknown_policy.diagonal_policy = "Sometimes".to_string();
+        assert!(matches!(
+            error(unknown_policy),
+            SaveFileError::UnknownDiagonalPolicy { .. }
+        ));
+
+        let mut outside = saved_map();
+        outside.goal = (3, 1);
+        assert!(matches!(
+            error(outside),
+            SaveFileError::OutOfBounds { name: "goal", .. }
+        ));
+    }
+}
+
+// References
+// 1. https://github.com/ron-rs/ron
+// 2. https://serde.rs/derive.html
@@ -1,13 +1,74 @@
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use bevy_rapier2d::prelude::*;
+use std::collections::{HashMap, HashSet};
 
 use super::{
-    GameState, Map, MapUpdatedEvent, PathfindingAlgorithm, PathfindingAlgorithmChangedEvent,
-    Position, MAP_HEIGHT, MAP_WIDTH,
+    GameState, Grid, HeatmapMode, MapConfig, MapUpdatedEvent, MovementProfiles,
+    PathfindingAlgorithm, PathfindingAlgorithmChangedEvent, Position, Search, TerrainRegistry,
+    TracedSearch,
 };
 
-pub const TILE_SIZE: i32 = 32;
+// The floor texture is white, so these tint it to show the state of a stepped search and the
+// path. Other terrains keep some of their own color under the tint.
+const PATH_COLOR: Color = Color::rgb(0.0, 0.36, 0.86);
+const OPEN_SET_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+const CLOSED_SET_COLOR: Color = Color::rgb(0.65, 0.75, 0.95);
+const CURRENT_NODE_COLOR: Color = Color::rgb(1.0, 0.45, 0.1);
+
+const PATH_SEGMENT_COLOR: Color = Color::rgb(0.05, 0.1, 0.45);
+const PATH_SEGMENT_WIDTH: f32 = 4.0;
+
+// The paths the other movement profiles found are drawn under the current one, one colour each.
+pub const PROFILE_PATH_COLORS: [Color; 4] = [
+    Color::rgb(0.3, 0.5, 1.0),
+    Color::rgb(0.9, 0.25, 0.2),
+    Color::rgb(0.2, 0.75, 0.3),
+    Color::rgb(0.8, 0.4, 0.9),
+];
+
+// The heatmap runs from the first or cheapest expanded tile to the last or most expensive one, and
+// marks the tiles that were still open when the search stopped.
+const HEATMAP_COLORS: [Color; 3] = [
+    Color::rgba(0.2, 0.2, 0.95, 0.6),
+    Color::rgba(0.95, 0.9, 0.2, 0.6),
+    Color::rgba(0.9, 0.15, 0.1, 0.6),
+];
+const HEATMAP_FRONTIER_COLOR: Color = Color::rgba(0.1, 0.9, 0.35, 0.8);
+const HEATMAP_SHADES: u16 = 100;
+
+const START_TEXTURE: u32 = 3;
+const GOAL_TEXTURE: u32 = 4;
+
+// tiles.png has a straight and a diagonal arrow, flipping them covers all eight directions.
+const ARROW_TEXTURE: u32 = 6;
+const DIAGONAL_ARROW_TEXTURE: u32 = 7;
+
+// The name of a tilemap and the depth it is drawn at.
+struct TilemapLayer {
+    name: &'static str,
+    z: f32,
+}
+
+// The heatmap covers the path tilemap, the flow field arrows cover the heatmap, and the cost
+// labels stay readable on top of everything.
+const PATH_LAYER: TilemapLayer = TilemapLayer {
+    name: "Path",
+    z: 0.0,
+};
+const HEATMAP_LAYER: TilemapLayer = TilemapLayer {
+    name: "Heatmap",
+    z: 0.25,
+};
+const FLOW_FIELD_LAYER: TilemapLayer = TilemapLayer {
+    name: "Flow Field",
+    z: 0.5,
+};
+const COSTS_LAYER: TilemapLayer = TilemapLayer {
+    name: "Cost",
+    z: 1.0,
+};
 
 /// === Components ===
 #[derive(Component)]
@@ -19,6 +80,9 @@ pub struct PathTileMap {}
 #[derive(Component)]
 pub struct PathTileMapStorage {}
 
+#[derive(Component)]
+pub struct PathSegment {}
+
 #[derive(Component)]
 pub struct CostsTile {}
 
@@ -28,162 +92,285 @@ pub struct CostsTileMap {}
 #[derive(Component)]
 pub struct CostsTileMapStorage {}
 
+#[derive(Component)]
+pub struct FlowFieldTile {}
+
+#[derive(Component)]
+pub struct FlowFieldTileMap {}
+
+#[derive(Component)]
+pub struct HeatmapTile {}
+
+#[derive(Component)]
+pub struct HeatmapTileMap {}
+
 /// === Helper Functions ===
 #[must_use]
-pub fn index_to_world_position(x: i32, y: i32) -> Vec2 {
-    let x_offset = (x * TILE_SIZE) + TILE_SIZE / 2;
-    let y_offset = (y * TILE_SIZE) + TILE_SIZE / 2;
+pub fn index_to_world_position(x: i32, y: i32, tile_size: i32) -> Vec2 {
+    let x_offset = (x * tile_size) + tile_size / 2;
+    let y_offset = (y * tile_size) + tile_size / 2;
     Vec2::new(x_offset as f32, y_offset as f32)
 }
 
 #[must_use]
-pub fn world_position_to_index(position: Vec2) -> (i32, i32) {
-    let x_index = position.x / TILE_SIZE as f32;
-    let y_index = position.y / TILE_SIZE as f32;
+pub fn world_position_to_index(position: Vec2, tile_size: i32) -> (i32, i32) {
+    let x_index = position.x / tile_size as f32;
+    let y_index = position.y / tile_size as f32;
     (x_index as i32, y_index as i32)
 }
 
-pub fn setup_path_tilemap(mut commands: Commands, asset_server: Res<AssetServer>) {
-    println!("Setup Path TileMap...");
-    let tilemap_size = TilemapSize {
-        x: MAP_WIDTH as u32,
-        y: MAP_HEIGHT as u32,
+// Blends between the heatmap colors by how far `value` is towards `max_value`.
+fn heatmap_color(value: usize, max_value: usize) -> Color {
+    let shade = u16::try_from(value * usize::from(HEATMAP_SHADES) / max_value.max(1))
+        .unwrap_or(HEATMAP_SHADES)
+        .min(HEATMAP_SHADES);
+    let t = f32::from(shade) / f32::from(HEATMAP_SHADES) * 2.0;
+    let (from, to, t) = if t < 1.0 {
+        (HEATMAP_COLORS[0], HEATMAP_COLORS[1], t)
+    } else {
+        (HEATMAP_COLORS[1], HEATMAP_COLORS[2], t - 1.0)
     };
-    let path_tilemap_entity = commands.spawn().id(); // Need one per layer.
-    let mut path_tile_storage = TileStorage::empty(tilemap_size); // Need one per tilemap_entity.
+    Color::rgba(
+        from.r() + (to.r() - from.r()) * t,
+        from.g() + (to.g() - from.g()) * t,
+        from.b() + (to.b() - from.b()) * t,
+        from.a() + (to.a() - from.a()) * t,
+    )
+}
 
-    // Spawn the elements of the tilemap.
-    for y in 0..tilemap_size.y {
-        for x in 0..tilemap_size.x {
-            let world_position = index_to_world_position(x as i32, y as i32);
-            let tile_position = TilePos { x, y };
-            let tile_entity = commands
-                .spawn()
-                .insert(Name::new(format!("Path Tile: {}, {}", x, y)))
-                .insert(PathTile {})
-                .insert_bundle(TileBundle {
-                    position: tile_position,
-                    tilemap_id: TilemapId(path_tilemap_entity),
-                    ..default()
-                })
-                .insert_bundle(TransformBundle {
-                    local: Transform::from_xyz(world_position.x, world_position.y, 0.),
-                    global: Default::default(),
-                })
+pub fn setup_path_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map_config: Res<MapConfig>,
+    map: Res<Grid>,
+) {
+    println!("Setup Path TileMap...");
+    spawn_path_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+}
+
+fn spawn_path_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &Grid,
+    tile_size: i32,
+) {
+    spawn_tilemap(
+        commands,
+        asset_server,
+        map,
+        tile_size,
+        &PATH_LAYER,
+        PathTileMap {},
+        |tile, world_position| {
+            tile.insert(PathTile {})
+                .insert_bundle(TransformBundle::from_transform(Transform::from_xyz(
+                    world_position.x,
+                    world_position.y,
+                    0.,
+                )))
                 .insert(RigidBody::Fixed)
                 .insert(Collider::cuboid(
-                    TILE_SIZE as f32 / 2.0,
-                    TILE_SIZE as f32 / 2.0,
-                ))
-                .id();
-            path_tile_storage.set(&tile_position, Some(tile_entity));
-        }
-    }
+                    tile_size as f32 / 2.0,
+                    tile_size as f32 / 2.0,
+                ));
+        },
+    );
+}
 
-    let tile_size = TilemapTileSize {
-        x: TILE_SIZE as f32,
-        y: TILE_SIZE as f32,
+pub fn setup_costs_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map_config: Res<MapConfig>,
+    map: Res<Grid>,
+) {
+    println!("Setup Costs TileMap...");
+    spawn_costs_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+}
+
+fn spawn_costs_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &Grid,
+    tile_size: i32,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans/FiraSans-Bold.ttf"),
+        font_size: 12.0,
+        color: Color::RED,
     };
-    let image_handle: Handle<Image> = asset_server.load("sprites/tiles.png");
+    spawn_tilemap(
+        commands,
+        asset_server,
+        map,
+        tile_size,
+        &COSTS_LAYER,
+        CostsTileMap {},
+        |tile, world_position| {
+            tile.insert(CostsTile {}).insert_bundle(Text2dBundle {
+                text: Text::from_section("0", text_style.clone())
+                    .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_xyz(world_position.x, world_position.y, 1.0),
+                ..default()
+            });
+        },
+    );
+}
 
-    let mut tilemap_entity_transform =
-        bevy_ecs_tilemap::helpers::get_centered_transform_2d(&tilemap_size, &tile_size, 0.0);
-    tilemap_entity_transform.translation.x += (MAP_WIDTH / 2) as f32;
-    tilemap_entity_transform.translation.y += (MAP_HEIGHT / 2) as f32;
+pub fn setup_flow_field_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map_config: Res<MapConfig>,
+    map: Res<Grid>,
+) {
+    println!("Setup Flow Field TileMap...");
+    spawn_flow_field_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+}
 
-    commands
-        .entity(path_tilemap_entity)
-        .insert(Name::new("Path Tilemap".to_string()))
-        .insert(PathTileMap {})
-        .insert_bundle(TilemapBundle {
-            grid_size: TilemapGridSize {
-                x: TILE_SIZE as f32,
-                y: TILE_SIZE as f32,
-            },
-            size: tilemap_size,
-            storage: path_tile_storage,
-            texture: TilemapTexture(image_handle),
-            tile_size,
-            transform: Transform::from_xyz(0.0, 0.0, 0.0),
-            ..Default::default()
-        });
+// The tiles stay empty until a flow field is drawn.
+fn spawn_flow_field_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &Grid,
+    tile_size: i32,
+) {
+    spawn_tilemap(
+        commands,
+        asset_server,
+        map,
+        tile_size,
+        &FLOW_FIELD_LAYER,
+        FlowFieldTileMap {},
+        |tile, _| {
+            tile.insert(FlowFieldTile {});
+        },
+    );
 }
 
-pub fn setup_costs_tilemap(mut commands: Commands, asset_server: Res<AssetServer>) {
-    println!("Setup Costs TileMap...");
+pub fn setup_heatmap_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map_config: Res<MapConfig>,
+    map: Res<Grid>,
+) {
+    println!("Setup Heatmap TileMap...");
+    spawn_heatmap_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+}
+
+// The tiles stay hidden until a search is drawn.
+fn spawn_heatmap_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &Grid,
+    tile_size: i32,
+) {
+    spawn_tilemap(
+        commands,
+        asset_server,
+        map,
+        tile_size,
+        &HEATMAP_LAYER,
+        HeatmapTileMap {},
+        |tile, _| {
+            tile.insert(HeatmapTile {}).insert(TileVisible(false));
+        },
+    );
+}
+
+// Spawns a tilemap over the whole map, with `marker` on the tilemap. `spawn_tile` adds the rest
+// of a tile to its entity, and is given the world position of the center of the tile.
+fn spawn_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &Grid,
+    tile_size: i32,
+    layer: &TilemapLayer,
+    marker: impl Component,
+    mut spawn_tile: impl FnMut(&mut EntityCommands, Vec2),
+) {
     let tilemap_size = TilemapSize {
-        x: MAP_WIDTH as u32,
-        y: MAP_HEIGHT as u32,
+        x: map.width.cast_unsigned(),
+        y: map.height.cast_unsigned(),
     };
     let tilemap_entity = commands.spawn().id(); // Need one per layer.
     let mut tile_storage = TileStorage::empty(tilemap_size); // Need one per tilemap_entity.
 
     // Spawn the elements of the tilemap.
-    for y in 0..tilemap_size.y {
-        for x in 0..tilemap_size.x {
-            let world_position = index_to_world_position(x as i32, y as i32);
-            let tile_position = TilePos { x, y };
-            let tile_entity = commands
-                .spawn()
-                .insert(Name::new(format!("Cost Tile: {}, {}", x, y)))
-                .insert(CostsTile {})
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let tile_position = TilePos::new(x.cast_unsigned(), y.cast_unsigned());
+            let mut tile = commands.spawn();
+            tile.insert(Name::new(format!("{} Tile: {x}, {y}", layer.name)))
                 .insert_bundle(TileBundle {
                     position: tile_position,
                     tilemap_id: TilemapId(tilemap_entity),
                     ..default()
-                })
-                .insert_bundle(Text2dBundle {
-                    text: Text::from_section(
-                        "0",
-                        TextStyle {
-                            font: asset_server.load("fonts/FiraSans/FiraSans-Bold.ttf"),
-                            font_size: 12.0,
-                            color: Color::RED,
-                        },
-                    )
-                    .with_alignment(TextAlignment::CENTER),
-                    transform: Transform::from_xyz(world_position.x, world_position.y, 1.0),
-                    ..default()
-                })
-                .id();
-            tile_storage.set(&tile_position, Some(tile_entity));
+                });
+            spawn_tile(&mut tile, index_to_world_position(x, y, tile_size));
+            tile_storage.set(&tile_position, Some(tile.id()));
         }
     }
 
-    let tile_size = TilemapTileSize {
-        x: TILE_SIZE as f32,
-        y: TILE_SIZE as f32,
-    };
-    let image_handle: Handle<Image> = asset_server.load("sprites/tiles.png");
-
-    let mut tilemap_entity_transform =
-        bevy_ecs_tilemap::helpers::get_centered_transform_2d(&tilemap_size, &tile_size, 0.0);
-    tilemap_entity_transform.translation.x += (MAP_WIDTH / 2) as f32;
-    tilemap_entity_transform.translation.y += (MAP_HEIGHT / 2) as f32;
-
+    let tile_size = tile_size as f32;
     commands
         .entity(tilemap_entity)
-        .insert(Name::new("Costs Tilemap".to_string()))
-        .insert(CostsTileMap {})
+        .insert(Name::new(format!("{} Tilemap", layer.name)))
+        .insert(marker)
         .insert_bundle(TilemapBundle {
             grid_size: TilemapGridSize {
-                x: TILE_SIZE as f32,
-                y: TILE_SIZE as f32,
+                x: tile_size,
+                y: tile_size,
             },
             size: tilemap_size,
             storage: tile_storage,
-            texture: TilemapTexture(image_handle),
-            tile_size,
-            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            texture: TilemapTexture(asset_server.load("sprites/tiles.png")),
+            tile_size: TilemapTileSize {
+                x: tile_size,
+                y: tile_size,
+            },
+            transform: Transform::from_xyz(0.0, 0.0, layer.z),
             ..Default::default()
         });
 }
 
+// Replaces all tilemaps with ones of the new size after the map was resized.
+pub fn rebuild_tilemaps_system(
+    mut commands: Commands,
+    mut pathfinding_algorithm_changed_event: EventWriter<PathfindingAlgorithmChangedEvent>,
+    tilemap_query: Query<(Entity, &TileStorage, &TilemapSize)>,
+    asset_server: Res<AssetServer>,
+    map_config: Res<MapConfig>,
+    map: Res<Grid>,
+) {
+    if !map.is_changed() {
+        return;
+    }
+    let resized = tilemap_query.iter().any(|(_, _, tilemap_size)| {
+        tilemap_size.x != map.width as u32 || tilemap_size.y != map.height as u32
+    });
+    if !resized {
+        return;
+    }
+    debug!("Rebuild TileMaps...");
+    for (tilemap_entity, tile_storage, _) in &tilemap_query {
+        for tile_entity in tile_storage.iter().flatten() {
+            commands.entity(*tile_entity).despawn_recursive();
+        }
+        commands.entity(tilemap_entity).despawn_recursive();
+    }
+    spawn_path_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+    spawn_costs_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+    spawn_flow_field_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+    spawn_heatmap_tilemap(&mut commands, &asset_server, &map, map_config.tile_size);
+    // Hides the new cost labels again if the current algorithm ignores costs.
+    pathfinding_algorithm_changed_event.send(PathfindingAlgorithmChangedEvent {});
+}
+
 pub fn draw_path_tilemap(
     mut map_updated_event_reader: EventReader<MapUpdatedEvent>,
     tile_storage_query: Query<&TileStorage, With<PathTileMap>>,
-    mut tile_texture_query: Query<&mut TileTexture, With<PathTile>>,
-    map: Res<Map>,
+    mut tile_texture_query: Query<(&mut TileTexture, &mut TileColor), With<PathTile>>,
+    map: Res<Grid>,
+    terrains: Res<TerrainRegistry>,
     game_state: Res<GameState>,
 ) {
     for _ in map_updated_event_reader.iter() {
@@ -192,68 +379,238 @@ pub fn draw_path_tilemap(
                 for i in 0..map.width {
                     let tile_position = TilePos::new(i as u32, j as u32);
                     if let Some(tile_entity) = tile_storage.get(&tile_position) {
-                        if let Ok(mut tile_texture) = tile_texture_query.get_mut(tile_entity) {
-                            let index = map.xy_idx(i, j);
-                            if map.blocked[index] {
-                                tile_texture.0 = 2;
-                            } else {
-                                tile_texture.0 = 1;
-                            }
+                        if let Ok((mut tile_texture, mut tile_color)) =
+                            tile_texture_query.get_mut(tile_entity)
+                        {
+                            let terrain = map.terrain[map.xy_idx(i, j)];
+                            tile_texture.0 = terrains
+                                .get(terrain)
+                                .map_or(0, |terrain| terrain.texture_index);
+                            tile_color.0 = Color::WHITE;
                         }
                     }
                 }
             }
-            if !game_state.path.is_empty() {
-                for i in 1..game_state.step {
-                    let point = game_state.path[i];
+            if let Some(search) = &game_state.search {
+                let search_tiles = search
+                    .closed_set()
+                    .into_iter()
+                    .map(|position| (position, CLOSED_SET_COLOR))
+                    .chain(
+                        search
+                            .open_set()
+                            .into_iter()
+                            .map(|position| (position, OPEN_SET_COLOR)),
+                    )
+                    .chain(
+                        search
+                            .current()
+                            .map(|position| (position, CURRENT_NODE_COLOR)),
+                    );
+                for (point, color) in search_tiles {
                     let tile_position = TilePos::new(point.0 as u32, point.1 as u32);
                     if let Some(tile_entity) = tile_storage.get(&tile_position) {
-                        if let Ok(mut tile_texture) = tile_texture_query.get_mut(tile_entity) {
-                            tile_texture.0 = 5;
+                        if let Ok((_, mut tile_color)) = tile_texture_query.get_mut(tile_entity) {
+                            tile_color.0 = color;
                         }
                     }
                 }
             }
-            // for point in &game_state.path {
-            //     let tile_position = TilePos::new(point.0 as u32, point.1 as u32);
-            //     if let Some(tile_entity) = tile_storage.get(&tile_position) {
-            //         if let Ok(mut tile_texture) = tile_texture_query.get_mut(tile_entity) {
-            //             tile_texture.0 = 5;
-            //         }
-            //     }
-            // }
+            for point in &game_state.path {
+                let tile_position = TilePos::new(point.0 as u32, point.1 as u32);
+                if let Some(tile_entity) = tile_storage.get(&tile_position) {
+                    if let Ok((_, mut tile_color)) = tile_texture_query.get_mut(tile_entity) {
+                        tile_color.0 = PATH_COLOR;
+                    }
+                }
+            }
             let start: &Position = &game_state.start;
             let tile_position = TilePos::new(start.0 as u32, start.1 as u32);
             if let Some(tile_entity) = tile_storage.get(&tile_position) {
-                if let Ok(mut tile_texture) = tile_texture_query.get_mut(tile_entity) {
-                    tile_texture.0 = 3;
+                if let Ok((mut tile_texture, mut tile_color)) =
+                    tile_texture_query.get_mut(tile_entity)
+                {
+                    tile_texture.0 = START_TEXTURE;
+                    tile_color.0 = Color::WHITE;
                 }
             }
             let goal: &Position = &game_state.goal;
             let tile_position = TilePos::new(goal.0 as u32, goal.1 as u32);
             if let Some(tile_entity) = tile_storage.get(&tile_position) {
-                if let Ok(mut tile_texture) = tile_texture_query.get_mut(tile_entity) {
-                    tile_texture.0 = 4;
+                if let Ok((mut tile_texture, mut tile_color)) =
+                    tile_texture_query.get_mut(tile_entity)
+                {
+                    tile_texture.0 = GOAL_TEXTURE;
+                    tile_color.0 = Color::WHITE;
                 }
             }
         }
     }
 }
 
+// Draws a straight line between each pair of waypoints, any-angle paths skip over many tiles.
+pub fn draw_path_segments(
+    mut commands: Commands,
+    mut map_updated_event_reader: EventReader<MapUpdatedEvent>,
+    path_segment_query: Query<Entity, With<PathSegment>>,
+    game_state: Res<GameState>,
+    movement_profiles: Res<MovementProfiles>,
+    map_config: Res<MapConfig>,
+) {
+    if map_updated_event_reader.iter().last().is_none() {
+        return;
+    }
+    for path_segment_entity in &path_segment_query {
+        commands.entity(path_segment_entity).despawn();
+    }
+    for (index, path) in movement_profiles.paths.iter().enumerate() {
+        // Only paths between the current start and goal are worth comparing.
+        if index == movement_profiles.selected
+            || path.first() != Some(&game_state.start)
+            || path.last() != Some(&game_state.goal)
+        {
+            continue;
+        }
+        let color = PROFILE_PATH_COLORS[index % PROFILE_PATH_COLORS.len()];
+        spawn_path_segments(&mut commands, path, color, 1.5, map_config.tile_size);
+    }
+    spawn_path_segments(
+        &mut commands,
+        &game_state.path,
+        PATH_SEGMENT_COLOR,
+        2.0,
+        map_config.tile_size,
+    );
+}
+
+fn spawn_path_segments(
+    commands: &mut Commands,
+    path: &[Position],
+    color: Color,
+    z: f32,
+    tile_size: i32,
+) {
+    for waypoints in path.windows(2) {
+        let from = index_to_world_position(waypoints[0].0, waypoints[0].1, tile_size);
+        let to = index_to_world_position(waypoints[1].0, waypoints[1].1, tile_size);
+        let segment = to - from;
+        let midpoint = (from + to) / 2.0;
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(segment.length(), PATH_SEGMENT_WIDTH)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(midpoint.x, midpoint.y, z)
+                    .with_rotation(Quat::from_rotation_z(segment.y.atan2(segment.x))),
+                ..default()
+            })
+            .insert(Name::new("Path Segment"))
+            .insert(PathSegment {});
+    }
+}
+
+// Draws an arrow on every tile the search or the last solved flow field has a direction for.
+pub fn draw_flow_field_tilemap(
+    mut map_updated_event_reader: EventReader<MapUpdatedEvent>,
+    mut flow_field_tile_query: Query<
+        (&TilePos, &mut TileTexture, &mut TileFlip),
+        With<FlowFieldTile>,
+    >,
+    game_state: Res<GameState>,
+) {
+    if map_updated_event_reader.iter().last().is_none() {
+        return;
+    }
+    for (tile_position, mut tile_texture, mut tile_flip) in &mut flow_field_tile_query {
+        let position = Position(tile_position.x as i32, tile_position.y as i32);
+        // A stepped flow field shows its directions while they are integrated, a solved one once
+        // it is done.
+        let direction = match (&game_state.search, &game_state.flow_field) {
+            (Some(search), _) => search.direction(position),
+            (None, Some(flow_field)) => flow_field.direction(position),
+            (None, None) => None,
+        };
+        let (texture, x, y, d) = match direction {
+            Some((1, 0)) => (ARROW_TEXTURE, false, false, false),
+            Some((-1, 0)) => (ARROW_TEXTURE, true, false, false),
+            Some((0, 1)) => (ARROW_TEXTURE, false, true, true),
+            Some((0, -1)) => (ARROW_TEXTURE, false, false, true),
+            Some((1, 1)) => (DIAGONAL_ARROW_TEXTURE, false, false, false),
+            Some((-1, 1)) => (DIAGONAL_ARROW_TEXTURE, true, false, false),
+            Some((1, -1)) => (DIAGONAL_ARROW_TEXTURE, false, true, false),
+            Some((-1, -1)) => (DIAGONAL_ARROW_TEXTURE, true, true, false),
+            _ => (0, false, false, false),
+        };
+        tile_texture.0 = texture;
+        *tile_flip = TileFlip { x, y, d };
+    }
+}
+
+// Colors every tile the last search expanded by when it did or by its g cost, so how an
+// algorithm explores the map stays visible after the solve, not just the path it found.
+pub fn draw_heatmap_tilemap(
+    mut map_updated_event_reader: EventReader<MapUpdatedEvent>,
+    mut heatmap_tile_query: Query<(&TilePos, &mut TileColor, &mut TileVisible), With<HeatmapTile>>,
+    game_state: Res<GameState>,
+) {
+    if map_updated_event_reader.iter().last().is_none() {
+        return;
+    }
+    let stepped_trace = game_state.search.as_ref().map(TracedSearch::trace);
+    let trace = stepped_trace.as_ref().or(game_state.trace.as_ref());
+    let values = match (game_state.heatmap_mode, trace) {
+        (HeatmapMode::ExpansionOrder, Some(trace)) => trace
+            .expansion_order
+            .iter()
+            .map(|(position, order)| (*position, *order))
+            .collect(),
+        (HeatmapMode::GCost, Some(trace)) => trace
+            .g_costs
+            .iter()
+            .map(|(position, g_cost)| (*position, usize::try_from(*g_cost).unwrap_or_default()))
+            .collect(),
+        _ => HashMap::new(),
+    };
+    let max_value = values.values().copied().max().unwrap_or_default();
+    let frontier: HashSet<Position> = match trace {
+        Some(trace) if game_state.heatmap_mode != HeatmapMode::Off => {
+            trace.frontier.iter().copied().collect()
+        }
+        _ => HashSet::new(),
+    };
+    for (tile_position, mut tile_color, mut tile_visible) in &mut heatmap_tile_query {
+        let position = Position(tile_position.x as i32, tile_position.y as i32);
+        // The start and goal stay uncovered, so they can still be told apart.
+        let color = if position == game_state.start || position == game_state.goal {
+            None
+        } else if frontier.contains(&position) {
+            Some(HEATMAP_FRONTIER_COLOR)
+        } else {
+            values
+                .get(&position)
+                .map(|value| heatmap_color(*value, max_value))
+        };
+        tile_visible.0 = color.is_some();
+        tile_color.0 = color.unwrap_or(Color::NONE);
+    }
+}
+
 pub fn update_cost_tilemap(
     mut map_updated_event_reader: EventReader<MapUpdatedEvent>,
     mut cost_tiles_query: Query<(&TilePos, &mut Text), With<CostsTile>>,
-    map: Res<Map>,
+    map: Res<Grid>,
 ) {
     for _ in map_updated_event_reader.iter() {
         for (tile_position, mut text) in cost_tiles_query.iter_mut() {
-            let x = tile_position.x as i32;
-            let y = tile_position.y as i32;
-            if let Some(cost) = map.costs[map.xy_idx(x, y)] {
-                text.sections[0].value = cost.to_string();
+            let position = Position(tile_position.x as i32, tile_position.y as i32);
+            // Blocked tiles can't be stepped onto, so their cost doesn't mean anything.
+            text.sections[0].value = if map.is_walkable(position.0, position.1) {
+                map.tile_cost(position).to_string()
             } else {
-                text.sections[0].value = "N/A".to_string();
-            }
+                String::new()
+            };
         }
     }
 }
@@ -265,7 +622,7 @@ pub fn show_hide_cost_tilemap(
 ) {
     for _ in pathfinding_algorithm_changed_event_reader.iter() {
         match game_state.pathfinding_algorithm {
-            PathfindingAlgorithm::BFS => {
+            PathfindingAlgorithm::BFS | PathfindingAlgorithm::BidirectionalBFS => {
                 for mut cost_tile_visibility in cost_tile_query.iter_mut() {
                     cost_tile_visibility.is_visible = false;
                 }
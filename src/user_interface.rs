@@ -2,15 +2,26 @@ use bevy::prelude::*;
 use bevy::ui::Display::Flex;
 
 use super::{
-    ClearEvent, CycleAlgorithmLeftEvent, CycleAlgorithmRightEvent, GameState, PathfindingAlgorithm,
-    PathfindingAlgorithmChangedEvent, PathfindingAlgorithmSelectionChangedEvent, PlacementMode,
-    ResetEvent, SolveEvent, StepEvent,
+    warn_if_inconsistent_heuristic, ClearEvent, CycleAlgorithmLeftEvent, CycleAlgorithmRightEvent,
+    CycleDiagonalPolicyEvent, CycleGeneratorLeftEvent, CycleGeneratorRightEvent,
+    CycleHeatmapModeEvent, CycleHeuristicLeftEvent, CycleHeuristicRightEvent,
+    CycleProfileLeftEvent, CycleProfileRightEvent, CycleTerrainLeftEvent, CycleTerrainRightEvent,
+    DecreaseWeightEvent, GameState, GenerateMapEvent, Grid, Heuristic, IncreaseWeightEvent,
+    LoadMapEvent, MapGeneration, MovementProfiles, PathfindingAlgorithm,
+    PathfindingAlgorithmChangedEvent, PathfindingAlgorithmSelectionChangedEvent,
+    PathfindingSettings, PlacementMode, ResetEvent, SaveMapEvent, SolveEvent, Solver, StepEvent,
+    TerrainRegistry, ToggleWallCostsEvent, PROFILE_PATH_COLORS,
 };
 
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
+// The epsilon of weighted A* and ARA* can be set from 1, the cheapest path, up to 5.
+const MIN_WEIGHT: f64 = 1.0;
+const MAX_WEIGHT: f64 = 5.0;
+const WEIGHT_STEP: f64 = 0.5;
+
 // === Components ===
 #[derive(Component, Debug)]
 pub struct OpenButton {}
@@ -36,12 +47,21 @@ pub struct StepButton {}
 #[derive(Component, Debug)]
 pub struct SolveButton {}
 
+#[derive(Component, Debug)]
+pub struct SolveButtonText {}
+
 #[derive(Component, Debug)]
 pub struct ResetButton {}
 
 #[derive(Component, Debug)]
 pub struct ClearButton {}
 
+#[derive(Component, Debug)]
+pub struct SaveButton {}
+
+#[derive(Component, Debug)]
+pub struct LoadButton {}
+
 #[derive(Component, Debug)]
 pub struct CurrentAlgorithmText {}
 
@@ -51,681 +71,849 @@ pub struct CycleAlgorithmLeftButton {}
 #[derive(Component, Debug)]
 pub struct CycleAlgorithmRightButton {}
 
+#[derive(Component, Debug)]
+pub struct GenerateButton {}
+
+#[derive(Component, Debug)]
+pub struct CurrentGeneratorText {}
+
+#[derive(Component, Debug)]
+pub struct WallCostsButton {}
+
+#[derive(Component, Debug)]
+pub struct WallCostsButtonText {}
+
+#[derive(Component, Debug)]
+pub struct DiagonalPolicyButton {}
+
+#[derive(Component, Debug)]
+pub struct DiagonalPolicyButtonText {}
+
+#[derive(Component, Debug)]
+pub struct HeatmapModeButton {}
+
+#[derive(Component, Debug)]
+pub struct HeatmapModeButtonText {}
+
+#[derive(Component, Debug)]
+pub struct CycleGeneratorLeftButton {}
+
+#[derive(Component, Debug)]
+pub struct CycleGeneratorRightButton {}
+
+#[derive(Component, Debug)]
+pub struct TerrainButton {}
+
+#[derive(Component, Debug)]
+pub struct TerrainButtonText {}
+
+#[derive(Component, Debug)]
+pub struct CycleTerrainLeftButton {}
+
+#[derive(Component, Debug)]
+pub struct CycleTerrainRightButton {}
+
+#[derive(Component, Debug)]
+pub struct CurrentHeuristicText {}
+
+#[derive(Component, Debug)]
+pub struct CycleHeuristicLeftButton {}
+
+#[derive(Component, Debug)]
+pub struct CycleHeuristicRightButton {}
+
+#[derive(Component, Debug)]
+pub struct CurrentWeightText {}
+
+#[derive(Component, Debug)]
+pub struct DecreaseWeightButton {}
+
+#[derive(Component, Debug)]
+pub struct IncreaseWeightButton {}
+
+#[derive(Component, Debug)]
+pub struct CurrentProfileText {}
+
+#[derive(Component, Debug)]
+pub struct CycleProfileLeftButton {}
+
+#[derive(Component, Debug)]
+pub struct CycleProfileRightButton {}
+
 // === Events ===
 pub struct UserInterfaceInteractionEvent {} // Empty Event
 
-// === Systems ===
-pub fn setup_user_interface(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // === Styles ===
-    let button_container_style = Style {
-        display: Flex,
-        flex_direction: FlexDirection::Row,
-        size: Size::new(Val::Auto, Val::Auto),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
-
-    let button_style = Style {
-        display: Flex,
-        flex_direction: FlexDirection::Row,
-        size: Size::new(Val::Auto, Val::Auto),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        margin: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
-        padding: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
-        ..default()
-    };
+// === Layout ===
+// The styles every button is spawned with.
+struct ButtonStyles {
+    container: Style,
+    button: Style,
+    text: TextStyle,
+}
 
-    let button_text_style = TextStyle {
-        font: asset_server.load("fonts/FiraSans/FiraSans-Bold.ttf"),
-        font_size: 16.0,
-        color: Color::rgb(0.9, 0.9, 0.9),
-    };
+// Spawns a button with a text label, and returns the button and its label.
+fn spawn_labeled_button(
+    commands: &mut Commands,
+    styles: &ButtonStyles,
+    name: &str,
+    label: &str,
+    marker: impl Component,
+) -> (Entity, Entity) {
+    let text = commands
+        .spawn_bundle(TextBundle::from_section(label, styles.text.clone()))
+        .id();
+    let button = commands
+        .spawn_bundle(ButtonBundle {
+            style: styles.button.clone(),
+            color: NORMAL_BUTTON.into(),
+            ..default()
+        })
+        .insert(Name::new(name.to_string()))
+        .insert(marker)
+        .push_children(&[text])
+        .id();
+    (button, text)
+}
 
-    // === Create UI ===
-    let root_container = commands
+// Spawns a labeled button in a container of its own to line up in a row of buttons, and returns
+// the container and the label.
+fn spawn_button(
+    commands: &mut Commands,
+    styles: &ButtonStyles,
+    name: &str,
+    label: &str,
+    marker: impl Component,
+) -> (Entity, Entity) {
+    let (button, text) =
+        spawn_labeled_button(commands, styles, &format!("{name} Button"), label, marker);
+    let container = commands
         .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Column,
-                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                justify_content: JustifyContent::Center,
-                ..default()
-            },
+            style: styles.container.clone(),
             color: Color::NONE.into(),
             ..default()
         })
-        .insert(Name::new("Root Container"))
+        .insert(Name::new(format!("{name} Button Container")))
+        .push_children(&[button])
         .id();
+    (container, text)
+}
 
-    let bottom_container = commands
+// Spawns `children` side by side on a background of the button color, like the arrows around
+// the selected algorithm, and returns its container.
+fn spawn_selector(
+    commands: &mut Commands,
+    styles: &ButtonStyles,
+    name: &str,
+    children: &[Entity],
+) -> Entity {
+    let background = commands
         .spawn_bundle(NodeBundle {
             style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Percent(100.0), Val::Percent(10.0)),
-                justify_content: JustifyContent::Center,
-                ..default()
+                margin: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
+                ..styles.container.clone()
             },
-            color: Color::NONE.into(),
+            color: NORMAL_BUTTON.into(),
             ..default()
         })
-        .insert(Name::new("Bottom Container"))
+        .insert(Name::new(format!("{name} Background")))
+        .push_children(children)
         .id();
-
-    let spacer = commands
+    commands
         .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
-                justify_content: JustifyContent::Center,
-                flex_grow: 1.0,
-                ..default()
-            },
+            style: styles.container.clone(),
             color: Color::NONE.into(),
             ..default()
         })
-        .insert(Name::new("Spacer"))
-        .id();
+        .insert(Name::new(format!("{name} Container")))
+        .push_children(&[background])
+        .id()
+}
+
+// Spawns a selector with arrow buttons on either side of `current`, which shows the selection.
+fn spawn_cycle_selector(
+    commands: &mut Commands,
+    styles: &ButtonStyles,
+    name: &str,
+    (left_marker, right_marker): (impl Component, impl Component),
+    current: Entity,
+) -> Entity {
+    let (left_button, _) = spawn_labeled_button(
+        commands,
+        styles,
+        &format!("Cycle {name} Left Button"),
+        "<--",
+        left_marker,
+    );
+    let (right_button, _) = spawn_labeled_button(
+        commands,
+        styles,
+        &format!("Cycle {name} Right Button"),
+        "-->",
+        right_marker,
+    );
+    spawn_selector(
+        commands,
+        styles,
+        &format!("{name} Cycler"),
+        &[left_button, current, right_button],
+    )
+}
 
-    let top_container = commands
+// Spawns a column across the screen, `height` percent of it high, with `children` stacked.
+fn spawn_column(commands: &mut Commands, name: &str, height: f32, children: &[Entity]) -> Entity {
+    commands
         .spawn_bundle(NodeBundle {
             style: Style {
                 display: Flex,
                 flex_direction: FlexDirection::Column,
-                size: Size::new(Val::Percent(100.0), Val::Percent(15.0)),
+                size: Size::new(Val::Percent(100.0), Val::Percent(height)),
                 justify_content: JustifyContent::Center,
                 ..default()
             },
             color: Color::NONE.into(),
             ..default()
         })
-        .insert(Name::new("Top Container"))
-        .id();
+        .insert(Name::new(name.to_string()))
+        .push_children(children)
+        .id()
+}
 
-    // Title
-    let title_container = commands
+// Spawns a row across the screen with `children` side by side.
+fn spawn_button_row(commands: &mut Commands, name: &str, children: &[Entity]) -> Entity {
+    commands
         .spawn_bundle(NodeBundle {
             style: Style {
                 display: Flex,
                 flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
+                size: Size::new(Val::Percent(100.0), Val::Percent(50.0)),
                 justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
                 ..default()
             },
             color: Color::NONE.into(),
             ..default()
         })
-        .insert(Name::new("Title Container"))
-        .id();
+        .insert(Name::new(name.to_string()))
+        .push_children(children)
+        .id()
+}
 
-    let title_background = commands
+// The buttons with the marker `T` whose interaction changed since the last frame.
+type ButtonInteractionQuery<'w, 's, T> =
+    Query<'w, 's, (&'static Interaction, &'static mut UiColor), (Changed<Interaction>, With<T>)>;
+
+// Moves `offset` entries away from `index` in a list of `count` entries, wrapping around.
+fn cycle_index(index: usize, count: usize, offset: i32) -> usize {
+    let count = i32::try_from(count.max(1)).unwrap_or(i32::MAX);
+    let index = i32::try_from(index).unwrap_or_default();
+    usize::try_from((index + offset).rem_euclid(count)).unwrap_or_default()
+}
+
+// === Systems ===
+pub fn setup_user_interface(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // === Styles ===
+    let styles = ButtonStyles {
+        container: Style {
+            display: Flex,
+            flex_direction: FlexDirection::Row,
+            size: Size::new(Val::Auto, Val::Auto),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        button: Style {
+            display: Flex,
+            flex_direction: FlexDirection::Row,
+            size: Size::new(Val::Auto, Val::Auto),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            margin: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
+            padding: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
+            ..default()
+        },
+        text: TextStyle {
+            font: asset_server.load("fonts/FiraSans/FiraSans-Bold.ttf"),
+            font_size: 16.0,
+            color: Color::rgb(0.9, 0.9, 0.9),
+        },
+    };
+
+    // === Create UI ===
+    let spacer = commands
         .spawn_bundle(NodeBundle {
             style: Style {
                 display: Flex,
                 flex_direction: FlexDirection::Row,
                 size: Size::new(Val::Auto, Val::Auto),
                 justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                margin: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
-                padding: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
+                flex_grow: 1.0,
                 ..default()
             },
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
+            color: Color::NONE.into(),
             ..default()
         })
-        .insert(Name::new("Title Background"))
+        .insert(Name::new("Spacer"))
         .id();
 
+    // Title
     let title_text = commands
         .spawn_bundle(TextBundle::from_section(
             "Bevy Pathfinding Example Project",
             TextStyle {
-                font: asset_server.load("fonts/FiraSans/FiraSans-Bold.ttf"),
                 font_size: 32.0,
-                color: Color::rgb(0.9, 0.9, 0.9),
+                ..styles.text.clone()
             },
         ))
         .id();
-
-    commands
-        .entity(title_background)
-        .push_children(&[title_text]);
-
-    commands
-        .entity(title_container)
-        .push_children(&[title_background]);
-
-    let top_buttons_container = commands
+    let title_background = commands
         .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Percent(100.0), Val::Percent(50.0)),
-                justify_content: JustifyContent::Center,
-                ..default()
-            },
-            color: Color::NONE.into(),
+            style: styles.button.clone(),
+            color: NORMAL_BUTTON.into(),
             ..default()
         })
-        .insert(Name::new("Top Buttons Container"))
+        .insert(Name::new("Title Background"))
+        .push_children(&[title_text])
         .id();
-
-    // Step Button
-    let step_button_container = commands
+    let title_container = commands
         .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
+            style: styles.container.clone(),
             color: Color::NONE.into(),
             ..default()
         })
-        .insert(Name::new("Step Button Container"))
-        .id();
-
-    let step_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Step Button"))
-        .insert(StepButton {})
+        .insert(Name::new("Title Container"))
+        .push_children(&[title_background])
         .id();
 
-    let step_button_text = commands
-        .spawn_bundle(TextBundle::from_section("Step", button_text_style.clone()))
-        .id();
+    let top_buttons_container = spawn_top_buttons(&mut commands, &styles);
+    let placement_buttons_container = spawn_placement_buttons(&mut commands, &styles);
+    let generation_buttons_container = spawn_generation_buttons(&mut commands, &styles);
+
+    let bottom_container = spawn_column(
+        &mut commands,
+        "Bottom Container",
+        20.0,
+        &[placement_buttons_container, generation_buttons_container],
+    );
+    let top_container = spawn_column(
+        &mut commands,
+        "Top Container",
+        15.0,
+        &[top_buttons_container, title_container],
+    );
+    spawn_column(
+        &mut commands,
+        "Root Container",
+        100.0,
+        &[bottom_container, spacer, top_container],
+    );
+}
 
+// The buttons to search the map with and the settings of the search.
+fn spawn_top_buttons(commands: &mut Commands, styles: &ButtonStyles) -> Entity {
+    // Top Buttons
+    let (step_button, _) = spawn_button(commands, styles, "Step", "Step", StepButton {});
+    let (solve_button, solve_button_text) =
+        spawn_button(commands, styles, "Solve", "Solve", SolveButton {});
     commands
-        .entity(step_button)
-        .push_children(&[step_button_text]);
-    commands
-        .entity(step_button_container)
-        .push_children(&[step_button]);
+        .entity(solve_button_text)
+        .insert(SolveButtonText {});
+    let (reset_button, _) = spawn_button(commands, styles, "Reset", "Reset", ResetButton {});
+    let (clear_button, _) = spawn_button(commands, styles, "Clear", "Clear", ClearButton {});
+    let (save_button, _) = spawn_button(commands, styles, "Save", "Save", SaveButton {});
+    let (load_button, _) = spawn_button(commands, styles, "Load", "Load", LoadButton {});
 
-    // Solve Button
-    let solve_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Solve Button Container"))
+    // Algorithm Cycler
+    let current_algorithm_text = commands
+        .spawn_bundle(TextBundle::from_section("BFS", styles.text.clone()))
+        .insert(CurrentAlgorithmText {})
         .id();
-
-    let solve_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Solve Button"))
-        .insert(SolveButton {})
+    let algorithm_cycler = spawn_cycle_selector(
+        commands,
+        styles,
+        "Algorithm",
+        (CycleAlgorithmLeftButton {}, CycleAlgorithmRightButton {}),
+        current_algorithm_text,
+    );
+
+    // Heuristic Cycler
+    let current_heuristic_text = commands
+        .spawn_bundle(TextBundle::from_section(
+            "Heuristic: Manhattan",
+            styles.text.clone(),
+        ))
+        .insert(CurrentHeuristicText {})
         .id();
-
-    let solve_button_text = commands
-        .spawn_bundle(TextBundle::from_section("Solve", button_text_style.clone()))
+    let heuristic_cycler = spawn_cycle_selector(
+        commands,
+        styles,
+        "Heuristic",
+        (CycleHeuristicLeftButton {}, CycleHeuristicRightButton {}),
+        current_heuristic_text,
+    );
+
+    // Weight Adjuster
+    let (decrease_weight_button, _) = spawn_labeled_button(
+        commands,
+        styles,
+        "Decrease Weight Button",
+        "-",
+        DecreaseWeightButton {},
+    );
+    let current_weight_text = commands
+        .spawn_bundle(TextBundle::from_section("ε = 2.0", styles.text.clone()))
+        .insert(CurrentWeightText {})
         .id();
-
-    commands
-        .entity(solve_button)
-        .push_children(&[solve_button_text]);
-    commands
-        .entity(solve_button_container)
-        .push_children(&[solve_button]);
-
-    // Reset Button
-    let reset_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Reset Button Container"))
+    let (increase_weight_button, _) = spawn_labeled_button(
+        commands,
+        styles,
+        "Increase Weight Button",
+        "+",
+        IncreaseWeightButton {},
+    );
+    let weight_adjuster = spawn_selector(
+        commands,
+        styles,
+        "Weight Adjuster",
+        &[
+            decrease_weight_button,
+            current_weight_text,
+            increase_weight_button,
+        ],
+    );
+
+    // Profile Cycler
+    let current_profile_text = commands
+        .spawn_bundle(TextBundle::from_section(
+            "Profile: Walker",
+            TextStyle {
+                color: PROFILE_PATH_COLORS[0],
+                ..styles.text.clone()
+            },
+        ))
+        .insert(CurrentProfileText {})
         .id();
+    let profile_cycler = spawn_cycle_selector(
+        commands,
+        styles,
+        "Profile",
+        (CycleProfileLeftButton {}, CycleProfileRightButton {}),
+        current_profile_text,
+    );
+
+    spawn_button_row(
+        commands,
+        "Top Buttons Container",
+        &[
+            step_button,
+            solve_button,
+            reset_button,
+            clear_button,
+            save_button,
+            load_button,
+            algorithm_cycler,
+            heuristic_cycler,
+            weight_adjuster,
+            profile_cycler,
+        ],
+    )
+}
 
-    let reset_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Reset Button"))
-        .insert(ResetButton {})
-        .id();
+// The buttons to pick what clicking on the map places.
+fn spawn_placement_buttons(commands: &mut Commands, styles: &ButtonStyles) -> Entity {
+    // Placement Buttons
+    let (open_button, _) = spawn_button(commands, styles, "Open", "Open", OpenButton {});
+    let (obstacle_button, _) =
+        spawn_button(commands, styles, "Obstacle", "Obstacle", ObstacleButton {});
+    let (origin_button, _) = spawn_button(commands, styles, "Origin", "Origin", OriginButton {});
+    let (goal_button, _) = spawn_button(commands, styles, "Goal", "Goal", GoalButton {});
+    let (increase_cost_button, _) = spawn_button(
+        commands,
+        styles,
+        "Increase Cost",
+        "+ Cost",
+        IncreaseCostButton {},
+    );
+    let (decrease_cost_button, _) = spawn_button(
+        commands,
+        styles,
+        "Decrease Cost",
+        "- Cost",
+        DecreaseCostButton {},
+    );
+
+    // Terrain Cycler
+    let (terrain_button, terrain_button_text) = spawn_labeled_button(
+        commands,
+        styles,
+        "Terrain Button",
+        "Terrain: Grass",
+        TerrainButton {},
+    );
+    commands
+        .entity(terrain_button_text)
+        .insert(TerrainButtonText {});
+    let terrain_cycler = spawn_cycle_selector(
+        commands,
+        styles,
+        "Terrain",
+        (CycleTerrainLeftButton {}, CycleTerrainRightButton {}),
+        terrain_button,
+    );
+
+    spawn_button_row(
+        commands,
+        "Placement Buttons Container",
+        &[
+            open_button,
+            obstacle_button,
+            origin_button,
+            goal_button,
+            increase_cost_button,
+            decrease_cost_button,
+            terrain_cycler,
+        ],
+    )
+}
 
-    let reset_button_text = commands
-        .spawn_bundle(TextBundle::from_section("Reset", button_text_style.clone()))
+// The buttons to generate a map and change how it is drawn.
+fn spawn_generation_buttons(commands: &mut Commands, styles: &ButtonStyles) -> Entity {
+    // Generator Cycler
+    let current_generator_text = commands
+        .spawn_bundle(TextBundle::from_section(
+            "Recursive Backtracker",
+            styles.text.clone(),
+        ))
+        .insert(CurrentGeneratorText {})
         .id();
-
+    let generator_cycler = spawn_cycle_selector(
+        commands,
+        styles,
+        "Generator",
+        (CycleGeneratorLeftButton {}, CycleGeneratorRightButton {}),
+        current_generator_text,
+    );
+
+    // Generation Buttons
+    let (generate_button, _) =
+        spawn_button(commands, styles, "Generate", "Generate", GenerateButton {});
+    let (wall_costs_button, wall_costs_button_text) = spawn_button(
+        commands,
+        styles,
+        "Wall Costs",
+        "Wall Costs: Off",
+        WallCostsButton {},
+    );
     commands
-        .entity(reset_button)
-        .push_children(&[reset_button_text]);
+        .entity(wall_costs_button_text)
+        .insert(WallCostsButtonText {});
+    let (diagonal_policy_button, diagonal_policy_button_text) = spawn_button(
+        commands,
+        styles,
+        "Diagonal Policy",
+        "Diagonals: Never",
+        DiagonalPolicyButton {},
+    );
     commands
-        .entity(reset_button_container)
-        .push_children(&[reset_button]);
-
-    // Clear Button
-    let clear_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Clear Button Container"))
-        .id();
+        .entity(diagonal_policy_button_text)
+        .insert(DiagonalPolicyButtonText {});
+    let (heatmap_mode_button, heatmap_mode_button_text) = spawn_button(
+        commands,
+        styles,
+        "Heatmap Mode",
+        "Heatmap: Off",
+        HeatmapModeButton {},
+    );
+    commands
+        .entity(heatmap_mode_button_text)
+        .insert(HeatmapModeButtonText {});
+
+    spawn_button_row(
+        commands,
+        "Generation Buttons Container",
+        &[
+            generator_cycler,
+            generate_button,
+            wall_costs_button,
+            diagonal_policy_button,
+            heatmap_mode_button,
+        ],
+    )
+}
 
-    let clear_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Clear Button"))
-        .insert(ClearButton {})
-        .id();
+pub fn open_button_system(
+    mut path_button_query: ButtonInteractionQuery<OpenButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut path_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                game_state.placement_mode = PlacementMode::Path;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    let clear_button_text = commands
-        .spawn_bundle(TextBundle::from_section("Clear", button_text_style.clone()))
-        .id();
+pub fn obstacle_button_system(
+    mut obstacle_button_query: ButtonInteractionQuery<ObstacleButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut obstacle_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                game_state.placement_mode = PlacementMode::Obstacle;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    commands
-        .entity(clear_button)
-        .push_children(&[clear_button_text]);
-    commands
-        .entity(clear_button_container)
-        .push_children(&[clear_button]);
+pub fn start_button_system(
+    mut start_button_query: ButtonInteractionQuery<OriginButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut start_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                game_state.placement_mode = PlacementMode::Start;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    // Algorithm Cycler
-    let algorithm_cycler_container = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .id();
-
-    let algorithm_cycler_background = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                margin: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
-                padding: UiRect::new(Val::Px(16.0), Val::Px(16.0), Val::Px(16.0), Val::Px(16.0)),
-                ..default()
-            },
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .id();
-
-    // Cycle Algorithm Left Button
-    let cycle_algorithm_left_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .id();
-
-    let cycle_algorithm_left_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Cycle Algorithm Left Button"))
-        .insert(CycleAlgorithmLeftButton {})
-        .id();
-
-    let cycle_algorithm_left_text = commands
-        .spawn_bundle(TextBundle::from_section("<--", button_text_style.clone()))
-        .id();
-
-    commands
-        .entity(cycle_algorithm_left_button)
-        .push_children(&[cycle_algorithm_left_text]);
-    commands
-        .entity(cycle_algorithm_left_button_container)
-        .push_children(&[cycle_algorithm_left_button]);
-
-    // Current Algorithm Text
-    let current_algorithm_container = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Current Algorithm Container"))
-        .id();
-
-    let current_algorithm_text = commands
-        .spawn_bundle(TextBundle::from_section(
-            "BFS",
-            TextStyle {
-                font: asset_server.load("fonts/FiraSans/FiraSans-Bold.ttf"),
-                font_size: 16.0,
-                color: Color::rgb(0.9, 0.9, 0.9),
-            },
-        ))
-        .insert(CurrentAlgorithmText {})
-        .id();
-
-    commands
-        .entity(current_algorithm_container)
-        .push_children(&[current_algorithm_text]);
-
-    // Cycle Algorithm Right Button
-    let cycle_algorithm_right_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                display: Flex,
-                flex_direction: FlexDirection::Row,
-                size: Size::new(Val::Auto, Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .id();
-
-    let cycle_algorithm_right_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Cycle Algorithm Right Button"))
-        .insert(CycleAlgorithmRightButton {})
-        .id();
-
-    let cycle_algorithm_right_text = commands
-        .spawn_bundle(TextBundle::from_section("-->", button_text_style.clone()))
-        .id();
-
-    commands
-        .entity(cycle_algorithm_right_button)
-        .push_children(&[cycle_algorithm_right_text]);
-    commands
-        .entity(cycle_algorithm_right_button_container)
-        .push_children(&[cycle_algorithm_right_button]);
-
-    commands
-        .entity(algorithm_cycler_background)
-        .push_children(&[
-            cycle_algorithm_left_button_container,
-            current_algorithm_container,
-            cycle_algorithm_right_button_container,
-        ]);
-
-    commands
-        .entity(algorithm_cycler_container)
-        .push_children(&[algorithm_cycler_background]);
-
-    // Open Button
-    let open_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Open Button Container"))
-        .id();
-
-    let open_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Open Button"))
-        .insert(OpenButton {})
-        .id();
-
-    let open_button_text = commands
-        .spawn_bundle(TextBundle::from_section("Open", button_text_style.clone()))
-        .id();
-
-    commands
-        .entity(open_button)
-        .push_children(&[open_button_text]);
-    commands
-        .entity(open_button_container)
-        .push_children(&[open_button]);
-
-    // Obstacle Button
-    let obstacle_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Obstacle Button Container"))
-        .id();
-
-    let obstacle_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Obstacle Button"))
-        .insert(ObstacleButton {})
-        .id();
-
-    let obstacle_button_text = commands
-        .spawn_bundle(TextBundle::from_section(
-            "Obstacle",
-            button_text_style.clone(),
-        ))
-        .id();
-
-    commands
-        .entity(obstacle_button)
-        .push_children(&[obstacle_button_text]);
-    commands
-        .entity(obstacle_button_container)
-        .push_children(&[obstacle_button]);
-
-    // Origin Button
-    let origin_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Origin Button Container"))
-        .id();
-
-    let origin_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Origin Button"))
-        .insert(OriginButton {})
-        .id();
-
-    let origin_button_text = commands
-        .spawn_bundle(TextBundle::from_section(
-            "Origin",
-            button_text_style.clone(),
-        ))
-        .id();
-
-    commands
-        .entity(origin_button)
-        .push_children(&[origin_button_text]);
-    commands
-        .entity(origin_button_container)
-        .push_children(&[origin_button]);
-
-    // Goal Button
-    let goal_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Goal Button Container"))
-        .id();
-
-    let goal_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Goal Button"))
-        .insert(GoalButton {})
-        .id();
-
-    let goal_button_text = commands
-        .spawn_bundle(TextBundle::from_section("Goal", button_text_style.clone()))
-        .id();
-
-    commands
-        .entity(goal_button)
-        .push_children(&[goal_button_text]);
-    commands
-        .entity(goal_button_container)
-        .push_children(&[goal_button]);
-
-    // Increase Cost Button
-    let increase_cost_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Increase Cost Button Container"))
-        .id();
-
-    let increase_cost_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Increase Cost Button"))
-        .insert(IncreaseCostButton {})
-        .id();
+pub fn goal_button_system(
+    mut goal_button_query: ButtonInteractionQuery<GoalButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut goal_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                game_state.placement_mode = PlacementMode::Goal;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    let increase_cost_button_text = commands
-        .spawn_bundle(TextBundle::from_section(
-            "+ Cost",
-            button_text_style.clone(),
-        ))
-        .id();
+pub fn increase_cost_button_system(
+    mut increase_cost_button_query: ButtonInteractionQuery<IncreaseCostButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut increase_cost_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                game_state.placement_mode = PlacementMode::IncreaseCost;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    commands
-        .entity(increase_cost_button)
-        .push_children(&[increase_cost_button_text]);
-    commands
-        .entity(increase_cost_button_container)
-        .push_children(&[increase_cost_button]);
+pub fn decrease_cost_button_system(
+    mut decrease_cost_button_query: ButtonInteractionQuery<DecreaseCostButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut decrease_cost_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                game_state.placement_mode = PlacementMode::DecreaseCost;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    // Decrease Cost Button
-    let decrease_cost_button_container = commands
-        .spawn_bundle(NodeBundle {
-            style: button_container_style.clone(),
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(Name::new("Decrease Cost Button Container"))
-        .id();
+pub fn show_hide_increase_decrease_cost_buttons(
+    mut pathfinding_algorithm_changed_event_reader: EventReader<PathfindingAlgorithmChangedEvent>,
+    mut increase_cost_button_query: Query<
+        &mut Visibility,
+        (With<IncreaseCostButton>, Without<DecreaseCostButton>),
+    >,
+    mut decrease_cost_button_query: Query<
+        &mut Visibility,
+        (With<DecreaseCostButton>, Without<IncreaseCostButton>),
+    >,
+    game_state: Res<GameState>,
+) {
+    for _ in pathfinding_algorithm_changed_event_reader.iter() {
+        // BFS ignores costs, so there is no point in editing them.
+        let is_visible = !matches!(
+            game_state.pathfinding_algorithm,
+            PathfindingAlgorithm::BFS | PathfindingAlgorithm::BidirectionalBFS
+        );
+        for mut visibility in &mut increase_cost_button_query {
+            visibility.is_visible = is_visible;
+        }
+        for mut visibility in &mut decrease_cost_button_query {
+            visibility.is_visible = is_visible;
+        }
+    }
+}
 
-    let decrease_cost_button = commands
-        .spawn_bundle(ButtonBundle {
-            style: button_style.clone(),
-            color: Color::rgb(0.15, 0.15, 0.15).into(),
-            ..default()
-        })
-        .insert(Name::new("Decrease Cost Button"))
-        .insert(DecreaseCostButton {})
-        .id();
+pub fn step_button_system(
+    mut step_event_writer: EventWriter<StepEvent>,
+    mut step_button_query: ButtonInteractionQuery<StepButton>,
+) {
+    for (interaction, mut color) in &mut step_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                step_event_writer.send(StepEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    let decrease_cost_button_text = commands
-        .spawn_bundle(TextBundle::from_section(
-            "- Cost",
-            button_text_style.clone(),
-        ))
-        .id();
+pub fn solve_button_system(
+    mut solve_event_writer: EventWriter<SolveEvent>,
+    mut solve_button_query: ButtonInteractionQuery<SolveButton>,
+) {
+    for (interaction, mut color) in &mut solve_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                solve_event_writer.send(SolveEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    commands
-        .entity(decrease_cost_button)
-        .push_children(&[decrease_cost_button_text]);
-    commands
-        .entity(decrease_cost_button_container)
-        .push_children(&[decrease_cost_button]);
-
-    commands.entity(bottom_container).push_children(&[
-        open_button_container,
-        obstacle_button_container,
-        origin_button_container,
-        goal_button_container,
-        increase_cost_button_container,
-        decrease_cost_button_container,
-    ]);
-
-    commands.entity(top_buttons_container).push_children(&[
-        step_button_container,
-        solve_button_container,
-        reset_button_container,
-        clear_button_container,
-        algorithm_cycler_container,
-    ]);
+pub fn reset_button_system(
+    mut reset_event_writer: EventWriter<ResetEvent>,
+    mut reset_button_query: ButtonInteractionQuery<ResetButton>,
+) {
+    for (interaction, mut color) in &mut reset_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                reset_event_writer.send(ResetEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    commands
-        .entity(top_container)
-        .push_children(&[top_buttons_container, title_container]);
+pub fn clear_button_system(
+    mut clear_event_writer: EventWriter<ClearEvent>,
+    mut reset_button_query: ButtonInteractionQuery<ClearButton>,
+) {
+    for (interaction, mut color) in &mut reset_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                clear_event_writer.send(ClearEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
 
-    commands
-        .entity(root_container)
-        .push_children(&[bottom_container, spacer, top_container]);
+pub fn wall_costs_button_system(
+    mut toggle_wall_costs_event_writer: EventWriter<ToggleWallCostsEvent>,
+    mut wall_costs_button_query: ButtonInteractionQuery<WallCostsButton>,
+) {
+    for (interaction, mut color) in &mut wall_costs_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                toggle_wall_costs_event_writer.send(ToggleWallCostsEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
 }
 
-pub fn open_button_system(
-    mut path_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<OpenButton>),
-    >,
-    mut game_state: ResMut<GameState>,
+pub fn diagonal_policy_button_system(
+    mut cycle_diagonal_policy_event_writer: EventWriter<CycleDiagonalPolicyEvent>,
+    mut diagonal_policy_button_query: ButtonInteractionQuery<DiagonalPolicyButton>,
+) {
+    for (interaction, mut color) in &mut diagonal_policy_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                cycle_diagonal_policy_event_writer.send(CycleDiagonalPolicyEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+pub fn heatmap_mode_button_system(
+    mut cycle_heatmap_mode_event_writer: EventWriter<CycleHeatmapModeEvent>,
+    mut heatmap_mode_button_query: ButtonInteractionQuery<HeatmapModeButton>,
 ) {
-    for (interaction, mut color) in path_button_query.iter_mut() {
+    for (interaction, mut color) in &mut heatmap_mode_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                game_state.placement_mode = PlacementMode::Path;
+                cycle_heatmap_mode_event_writer.send(CycleHeatmapModeEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -737,18 +925,15 @@ pub fn open_button_system(
     }
 }
 
-pub fn obstacle_button_system(
-    mut obstacle_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<ObstacleButton>),
-    >,
-    mut game_state: ResMut<GameState>,
+pub fn generate_button_system(
+    mut generate_map_event_writer: EventWriter<GenerateMapEvent>,
+    mut generate_button_query: ButtonInteractionQuery<GenerateButton>,
 ) {
-    for (interaction, mut color) in obstacle_button_query.iter_mut() {
+    for (interaction, mut color) in &mut generate_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                game_state.placement_mode = PlacementMode::Obstacle;
+                generate_map_event_writer.send(GenerateMapEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -760,18 +945,15 @@ pub fn obstacle_button_system(
     }
 }
 
-pub fn start_button_system(
-    mut start_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<OriginButton>),
-    >,
-    mut game_state: ResMut<GameState>,
+pub fn save_button_system(
+    mut save_map_event_writer: EventWriter<SaveMapEvent>,
+    mut save_button_query: ButtonInteractionQuery<SaveButton>,
 ) {
-    for (interaction, mut color) in start_button_query.iter_mut() {
+    for (interaction, mut color) in &mut save_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                game_state.placement_mode = PlacementMode::Start;
+                save_map_event_writer.send(SaveMapEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -783,18 +965,15 @@ pub fn start_button_system(
     }
 }
 
-pub fn goal_button_system(
-    mut goal_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<GoalButton>),
-    >,
-    mut game_state: ResMut<GameState>,
+pub fn load_button_system(
+    mut load_map_event_writer: EventWriter<LoadMapEvent>,
+    mut load_button_query: ButtonInteractionQuery<LoadButton>,
 ) {
-    for (interaction, mut color) in goal_button_query.iter_mut() {
+    for (interaction, mut color) in &mut load_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                game_state.placement_mode = PlacementMode::Goal;
+                load_map_event_writer.send(LoadMapEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -806,17 +985,15 @@ pub fn goal_button_system(
     }
 }
 
-pub fn increase_cost_button_system(
-    mut increase_cost_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<IncreaseCostButton>),
-    >,
-    mut game_state: ResMut<GameState>,
+pub fn cycle_algorithm_left_button_system(
+    mut cycle_algorithm_left_event_writer: EventWriter<CycleAlgorithmLeftEvent>,
+    mut cycle_algorithm_left_button_query: ButtonInteractionQuery<CycleAlgorithmLeftButton>,
 ) {
-    for (interaction, mut color) in increase_cost_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_algorithm_left_button_query {
         match *interaction {
             Interaction::Clicked => {
-                game_state.placement_mode = PlacementMode::IncreaseCost;
+                *color = PRESSED_BUTTON.into();
+                cycle_algorithm_left_event_writer.send(CycleAlgorithmLeftEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -828,18 +1005,15 @@ pub fn increase_cost_button_system(
     }
 }
 
-pub fn decrease_cost_button_system(
-    mut decrease_cost_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<DecreaseCostButton>),
-    >,
-    mut game_state: ResMut<GameState>,
+pub fn cycle_algorithm_right_button_system(
+    mut cycle_algorithm_right_event_writer: EventWriter<CycleAlgorithmRightEvent>,
+    mut cycle_algorithm_right_button_query: ButtonInteractionQuery<CycleAlgorithmRightButton>,
 ) {
-    for (interaction, mut color) in decrease_cost_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_algorithm_right_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                game_state.placement_mode = PlacementMode::DecreaseCost;
+                cycle_algorithm_right_event_writer.send(CycleAlgorithmRightEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -851,52 +1025,94 @@ pub fn decrease_cost_button_system(
     }
 }
 
-pub fn show_hide_increase_decrease_cost_buttons(
-    mut pathfinding_algorithm_changed_event_reader: EventReader<PathfindingAlgorithmChangedEvent>,
-    mut increase_cost_button_query: Query<
-        &mut Visibility,
-        (With<IncreaseCostButton>, Without<DecreaseCostButton>),
-    >,
-    mut decrease_cost_button_query: Query<
-        &mut Visibility,
-        (With<DecreaseCostButton>, Without<IncreaseCostButton>),
+pub fn cycle_algorithm_selection_system(
+    mut cycle_algorithm_left_event_reader: EventReader<CycleAlgorithmLeftEvent>,
+    mut cycle_algorithm_right_event_reader: EventReader<CycleAlgorithmRightEvent>,
+    mut pathfinding_algorithm_selection_changed_event_writer: EventWriter<
+        PathfindingAlgorithmSelectionChangedEvent,
     >,
     game_state: Res<GameState>,
 ) {
-    for _ in pathfinding_algorithm_changed_event_reader.iter() {
-        match game_state.pathfinding_algorithm {
-            PathfindingAlgorithm::BFS => {
-                for mut visibility in increase_cost_button_query.iter_mut() {
-                    visibility.is_visible = false;
-                }
-                for mut visibility in decrease_cost_button_query.iter_mut() {
-                    visibility.is_visible = false;
-                }
-            }
-            _ => {
-                for mut visibility in increase_cost_button_query.iter_mut() {
-                    visibility.is_visible = true;
-                }
-                for mut visibility in decrease_cost_button_query.iter_mut() {
-                    visibility.is_visible = true;
-                }
+    let mut offset = 0;
+    for _ in cycle_algorithm_left_event_reader.iter() {
+        offset -= 1;
+    }
+    for _ in cycle_algorithm_right_event_reader.iter() {
+        offset += 1;
+    }
+    if offset == 0 {
+        return;
+    }
+    let index = PathfindingAlgorithm::ALL
+        .iter()
+        .position(|pathfinding_algorithm| {
+            *pathfinding_algorithm == game_state.pathfinding_algorithm
+        })
+        .unwrap_or(0);
+    pathfinding_algorithm_selection_changed_event_writer.send(
+        PathfindingAlgorithmSelectionChangedEvent {
+            pathfinding_algorithm: PathfindingAlgorithm::ALL
+                [cycle_index(index, PathfindingAlgorithm::ALL.len(), offset)],
+        },
+    );
+}
+
+// Weighted A* and ARA* show how many times more than the cheapest path their path may cost.
+pub fn update_current_algorithm_text_system(
+    mut current_algorithm_text_query: Query<&mut Text, With<CurrentAlgorithmText>>,
+    game_state: Res<GameState>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    let pathfinding_algorithm = game_state.pathfinding_algorithm;
+    let label = match pathfinding_algorithm {
+        // The bound of the path ARA* found so far, otherwise the epsilon the search starts with.
+        PathfindingAlgorithm::ARAStar if !game_state.path.is_empty() => {
+            let bound = game_state.suboptimality_bound.unwrap_or(1.0);
+            format!("{pathfinding_algorithm} (ε ≤ {bound:.2})")
+        }
+        PathfindingAlgorithm::WeightedAStar | PathfindingAlgorithm::ARAStar => {
+            format!("{pathfinding_algorithm} (ε = {:.1})", game_state.weight)
+        }
+        _ => pathfinding_algorithm.to_string(),
+    };
+    for mut text in &mut current_algorithm_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+pub fn cycle_generator_left_button_system(
+    mut cycle_generator_left_event_writer: EventWriter<CycleGeneratorLeftEvent>,
+    mut cycle_generator_left_button_query: ButtonInteractionQuery<CycleGeneratorLeftButton>,
+) {
+    for (interaction, mut color) in &mut cycle_generator_left_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                cycle_generator_left_event_writer.send(CycleGeneratorLeftEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
             }
         }
     }
 }
 
-pub fn step_button_system(
-    mut step_event_writer: EventWriter<StepEvent>,
-    mut step_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<StepButton>),
-    >,
+pub fn cycle_generator_right_button_system(
+    mut cycle_generator_right_event_writer: EventWriter<CycleGeneratorRightEvent>,
+    mut cycle_generator_right_button_query: ButtonInteractionQuery<CycleGeneratorRightButton>,
 ) {
-    for (interaction, mut color) in step_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_generator_right_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                step_event_writer.send(StepEvent {});
+                cycle_generator_right_event_writer.send(CycleGeneratorRightEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -908,18 +1124,77 @@ pub fn step_button_system(
     }
 }
 
-pub fn solve_button_system(
-    mut solve_event_writer: EventWriter<SolveEvent>,
-    mut solve_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<SolveButton>),
-    >,
+pub fn update_current_generator_text_system(
+    mut current_generator_text_query: Query<&mut Text, With<CurrentGeneratorText>>,
+    map_generation: Res<MapGeneration>,
+) {
+    if !map_generation.is_changed() {
+        return;
+    }
+    for mut text in &mut current_generator_text_query {
+        text.sections[0].value = map_generation.generator.to_string();
+    }
+}
+
+pub fn update_wall_costs_button_text_system(
+    mut wall_costs_button_text_query: Query<&mut Text, With<WallCostsButtonText>>,
+    map_generation: Res<MapGeneration>,
+) {
+    if !map_generation.is_changed() {
+        return;
+    }
+    let label = if map_generation.costs_near_walls {
+        "Wall Costs: On"
+    } else {
+        "Wall Costs: Off"
+    };
+    for mut text in &mut wall_costs_button_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.to_string();
+        }
+    }
+}
+
+// Loading a map can change the policy as well, so this follows the map instead of the button.
+pub fn update_diagonal_policy_button_text_system(
+    mut diagonal_policy_button_text_query: Query<&mut Text, With<DiagonalPolicyButtonText>>,
+    map: Res<Grid>,
 ) {
-    for (interaction, mut color) in solve_button_query.iter_mut() {
+    if !map.is_changed() {
+        return;
+    }
+    let label = format!("Diagonals: {}", map.diagonal_policy);
+    for mut text in &mut diagonal_policy_button_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+pub fn update_heatmap_mode_button_text_system(
+    mut heatmap_mode_button_text_query: Query<&mut Text, With<HeatmapModeButtonText>>,
+    game_state: Res<GameState>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    let label = format!("Heatmap: {}", game_state.heatmap_mode);
+    for mut text in &mut heatmap_mode_button_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+pub fn terrain_button_system(
+    mut terrain_button_query: ButtonInteractionQuery<TerrainButton>,
+    mut game_state: ResMut<GameState>,
+) {
+    for (interaction, mut color) in &mut terrain_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                solve_event_writer.send(SolveEvent {});
+                game_state.placement_mode = PlacementMode::Terrain(game_state.terrain_brush);
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -931,18 +1206,15 @@ pub fn solve_button_system(
     }
 }
 
-pub fn reset_button_system(
-    mut reset_event_writer: EventWriter<ResetEvent>,
-    mut reset_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<ResetButton>),
-    >,
+pub fn cycle_terrain_left_button_system(
+    mut cycle_terrain_left_event_writer: EventWriter<CycleTerrainLeftEvent>,
+    mut cycle_terrain_left_button_query: ButtonInteractionQuery<CycleTerrainLeftButton>,
 ) {
-    for (interaction, mut color) in reset_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_terrain_left_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                reset_event_writer.send(ResetEvent {});
+                cycle_terrain_left_event_writer.send(CycleTerrainLeftEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -954,18 +1226,15 @@ pub fn reset_button_system(
     }
 }
 
-pub fn clear_button_system(
-    mut clear_event_writer: EventWriter<ClearEvent>,
-    mut reset_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<ClearButton>),
-    >,
+pub fn cycle_terrain_right_button_system(
+    mut cycle_terrain_right_event_writer: EventWriter<CycleTerrainRightEvent>,
+    mut cycle_terrain_right_button_query: ButtonInteractionQuery<CycleTerrainRightButton>,
 ) {
-    for (interaction, mut color) in reset_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_terrain_right_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                clear_event_writer.send(ClearEvent {});
+                cycle_terrain_right_event_writer.send(CycleTerrainRightEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -977,18 +1246,55 @@ pub fn clear_button_system(
     }
 }
 
-pub fn cycle_algorithm_left_button_system(
-    mut cycle_algorithm_left_event_writer: EventWriter<CycleAlgorithmLeftEvent>,
-    mut cycle_algorithm_left_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<CycleAlgorithmLeftButton>),
-    >,
+// Picking a terrain also switches to painting it.
+pub fn cycle_terrain_selection_system(
+    mut cycle_terrain_left_event_reader: EventReader<CycleTerrainLeftEvent>,
+    mut cycle_terrain_right_event_reader: EventReader<CycleTerrainRightEvent>,
+    terrains: Res<TerrainRegistry>,
+    mut game_state: ResMut<GameState>,
+) {
+    let mut offset = 0;
+    for _ in cycle_terrain_left_event_reader.iter() {
+        offset -= 1;
+    }
+    for _ in cycle_terrain_right_event_reader.iter() {
+        offset += 1;
+    }
+    if offset == 0 {
+        return;
+    }
+    game_state.terrain_brush = terrains.cycle(game_state.terrain_brush, offset);
+    game_state.placement_mode = PlacementMode::Terrain(game_state.terrain_brush);
+}
+
+pub fn update_terrain_button_text_system(
+    mut terrain_button_text_query: Query<&mut Text, With<TerrainButtonText>>,
+    terrains: Res<TerrainRegistry>,
+    game_state: Res<GameState>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    let Some(terrain) = terrains.get(game_state.terrain_brush) else {
+        return;
+    };
+    let label = format!("Terrain: {}", terrain.name);
+    for mut text in &mut terrain_button_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+pub fn cycle_heuristic_left_button_system(
+    mut cycle_heuristic_left_event_writer: EventWriter<CycleHeuristicLeftEvent>,
+    mut cycle_heuristic_left_button_query: ButtonInteractionQuery<CycleHeuristicLeftButton>,
 ) {
-    for (interaction, mut color) in cycle_algorithm_left_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_heuristic_left_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                cycle_algorithm_left_event_writer.send(CycleAlgorithmLeftEvent {});
+                cycle_heuristic_left_event_writer.send(CycleHeuristicLeftEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -1000,18 +1306,15 @@ pub fn cycle_algorithm_left_button_system(
     }
 }
 
-pub fn cycle_algorithm_right_button_system(
-    mut cycle_algorithm_right_event_writer: EventWriter<CycleAlgorithmRightEvent>,
-    mut cycle_algorithm_right_button_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<CycleAlgorithmRightButton>),
-    >,
+pub fn cycle_heuristic_right_button_system(
+    mut cycle_heuristic_right_event_writer: EventWriter<CycleHeuristicRightEvent>,
+    mut cycle_heuristic_right_button_query: ButtonInteractionQuery<CycleHeuristicRightButton>,
 ) {
-    for (interaction, mut color) in cycle_algorithm_right_button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_heuristic_right_button_query {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
-                cycle_algorithm_right_event_writer.send(CycleAlgorithmRightEvent {});
+                cycle_heuristic_right_event_writer.send(CycleHeuristicRightEvent {});
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -1023,86 +1326,253 @@ pub fn cycle_algorithm_right_button_system(
     }
 }
 
-pub fn cycle_algorithm_selection_system(
-    mut cycle_algorithm_left_event_reader: EventReader<CycleAlgorithmLeftEvent>,
-    mut cycle_algorithm_right_event_reader: EventReader<CycleAlgorithmRightEvent>,
-    mut pathfinding_algorithm_selection_changed_event_writer: EventWriter<
-        PathfindingAlgorithmSelectionChangedEvent,
-    >,
+// Picks another heuristic, the current path is reset since it was found with the old one.
+pub fn cycle_heuristic_selection_system(
+    mut cycle_heuristic_left_event_reader: EventReader<CycleHeuristicLeftEvent>,
+    mut cycle_heuristic_right_event_reader: EventReader<CycleHeuristicRightEvent>,
+    mut reset_event_writer: EventWriter<ResetEvent>,
+    mut game_state: ResMut<GameState>,
+    map: Res<Grid>,
+) {
+    let mut offset = 0;
+    for _ in cycle_heuristic_left_event_reader.iter() {
+        offset -= 1;
+    }
+    for _ in cycle_heuristic_right_event_reader.iter() {
+        offset += 1;
+    }
+    if offset == 0 {
+        return;
+    }
+    let index = Heuristic::ALL
+        .iter()
+        .position(|heuristic| *heuristic == game_state.heuristic)
+        .unwrap_or(0);
+    game_state.heuristic = Heuristic::ALL[cycle_index(index, Heuristic::ALL.len(), offset)];
+    game_state.path = Vec::new();
+    println!("Heuristic: {}", game_state.heuristic);
+    let diagonal_policy = game_state
+        .movement_profile
+        .diagonal_policy
+        .unwrap_or(map.diagonal_policy);
+    warn_if_inconsistent_heuristic(&game_state.heuristic_algorithm(), diagonal_policy);
+    reset_event_writer.send(ResetEvent {});
+}
+
+pub fn update_current_heuristic_text_system(
+    mut current_heuristic_text_query: Query<&mut Text, With<CurrentHeuristicText>>,
     game_state: Res<GameState>,
 ) {
-    for _ in cycle_algorithm_left_event_reader.iter() {
-        let new_pathfinding_algorithm;
-        match game_state.pathfinding_algorithm {
-            PathfindingAlgorithm::AStar => {
-                new_pathfinding_algorithm = PathfindingAlgorithm::Dijkstra
+    if !game_state.is_changed() {
+        return;
+    }
+    let label = format!("Heuristic: {}", game_state.heuristic);
+    for mut text in &mut current_heuristic_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+pub fn decrease_weight_button_system(
+    mut decrease_weight_event_writer: EventWriter<DecreaseWeightEvent>,
+    mut decrease_weight_button_query: ButtonInteractionQuery<DecreaseWeightButton>,
+) {
+    for (interaction, mut color) in &mut decrease_weight_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                decrease_weight_event_writer.send(DecreaseWeightEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
             }
-            PathfindingAlgorithm::BFS => new_pathfinding_algorithm = PathfindingAlgorithm::AStar,
-            PathfindingAlgorithm::Dijkstra => new_pathfinding_algorithm = PathfindingAlgorithm::BFS,
         }
-        pathfinding_algorithm_selection_changed_event_writer.send(
-            PathfindingAlgorithmSelectionChangedEvent {
-                pathfinding_algorithm: new_pathfinding_algorithm,
-            },
-        );
     }
-    for _ in cycle_algorithm_right_event_reader.iter() {
-        let new_pathfinding_algorithm;
-        match game_state.pathfinding_algorithm {
-            PathfindingAlgorithm::AStar => new_pathfinding_algorithm = PathfindingAlgorithm::BFS,
-            PathfindingAlgorithm::BFS => new_pathfinding_algorithm = PathfindingAlgorithm::Dijkstra,
-            PathfindingAlgorithm::Dijkstra => {
-                new_pathfinding_algorithm = PathfindingAlgorithm::AStar
+}
+
+pub fn increase_weight_button_system(
+    mut increase_weight_event_writer: EventWriter<IncreaseWeightEvent>,
+    mut increase_weight_button_query: ButtonInteractionQuery<IncreaseWeightButton>,
+) {
+    for (interaction, mut color) in &mut increase_weight_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                increase_weight_event_writer.send(IncreaseWeightEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
             }
         }
-        pathfinding_algorithm_selection_changed_event_writer.send(
-            PathfindingAlgorithmSelectionChangedEvent {
-                pathfinding_algorithm: new_pathfinding_algorithm,
-            },
-        );
     }
 }
 
-pub fn update_current_algorithm_text_system(
-    mut pathfinding_algorithm_changed_event_reader: EventReader<PathfindingAlgorithmChangedEvent>,
-    mut current_algorithm_text_query: Query<&mut Text, With<CurrentAlgorithmText>>,
+// Changes the epsilon of weighted A* and ARA*, the current path is reset since it was found with
+// the old one.
+pub fn change_weight_system(
+    mut decrease_weight_event_reader: EventReader<DecreaseWeightEvent>,
+    mut increase_weight_event_reader: EventReader<IncreaseWeightEvent>,
+    mut reset_event_writer: EventWriter<ResetEvent>,
+    mut game_state: ResMut<GameState>,
+) {
+    let mut weight = game_state.weight;
+    for _ in decrease_weight_event_reader.iter() {
+        weight -= WEIGHT_STEP;
+    }
+    for _ in increase_weight_event_reader.iter() {
+        weight += WEIGHT_STEP;
+    }
+    let weight = weight.clamp(MIN_WEIGHT, MAX_WEIGHT);
+    if (weight - game_state.weight).abs() < f64::EPSILON {
+        return;
+    }
+    game_state.weight = weight;
+    game_state.path = Vec::new();
+    println!("Weight: {weight:.1}");
+    reset_event_writer.send(ResetEvent {});
+}
+
+pub fn update_current_weight_text_system(
+    mut current_weight_text_query: Query<&mut Text, With<CurrentWeightText>>,
     game_state: Res<GameState>,
 ) {
-    for _ in pathfinding_algorithm_changed_event_reader.iter() {
-        for mut text in &mut current_algorithm_text_query {
-            match game_state.pathfinding_algorithm {
-                PathfindingAlgorithm::AStar => {
-                    text.sections[0].value = "AStar".to_string();
-                }
-                PathfindingAlgorithm::BFS => {
-                    text.sections[0].value = "BFS".to_string();
-                }
-                PathfindingAlgorithm::Dijkstra => {
-                    text.sections[0].value = "Dijkstra".to_string();
-                }
+    if !game_state.is_changed() {
+        return;
+    }
+    let label = format!("ε = {:.1}", game_state.weight);
+    for mut text in &mut current_weight_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+pub fn cycle_profile_left_button_system(
+    mut cycle_profile_left_event_writer: EventWriter<CycleProfileLeftEvent>,
+    mut cycle_profile_left_button_query: ButtonInteractionQuery<CycleProfileLeftButton>,
+) {
+    for (interaction, mut color) in &mut cycle_profile_left_button_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                cycle_profile_left_event_writer.send(CycleProfileLeftEvent {});
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
             }
         }
     }
 }
 
-// This is a hack to solve my issue to ray casts going through buttons.
-// Every time a button is clicked or hovered over, I send a `UserInterfaceInteractionEvent`
-// Which other systems can read and then return from immediately.
-pub fn send_ui_interaction_events_system(
-    mut user_interface_interaction_event_writer: EventWriter<UserInterfaceInteractionEvent>,
-    mut button_query: Query<&Interaction, With<Button>>,
+pub fn cycle_profile_right_button_system(
+    mut cycle_profile_right_event_writer: EventWriter<CycleProfileRightEvent>,
+    mut cycle_profile_right_button_query: ButtonInteractionQuery<CycleProfileRightButton>,
 ) {
-    for interaction in button_query.iter_mut() {
+    for (interaction, mut color) in &mut cycle_profile_right_button_query {
         match *interaction {
             Interaction::Clicked => {
-                user_interface_interaction_event_writer.send(UserInterfaceInteractionEvent {});
+                *color = PRESSED_BUTTON.into();
+                cycle_profile_right_event_writer.send(CycleProfileRightEvent {});
             }
             Interaction::Hovered => {
-                user_interface_interaction_event_writer.send(UserInterfaceInteractionEvent {});
+                *color = HOVERED_BUTTON.into();
             }
-            _ => {
-                // Do Nothing
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
             }
         }
     }
 }
+
+// Picks another profile, the current path is reset since it was found with the old one. The
+// plugin gets the profile too, so it keeps the HPA* graph for the same grid the solves search.
+pub fn cycle_profile_selection_system(
+    mut cycle_profile_left_event_reader: EventReader<CycleProfileLeftEvent>,
+    mut cycle_profile_right_event_reader: EventReader<CycleProfileRightEvent>,
+    mut reset_event_writer: EventWriter<ResetEvent>,
+    mut movement_profiles: ResMut<MovementProfiles>,
+    mut game_state: ResMut<GameState>,
+    mut settings: ResMut<PathfindingSettings>,
+) {
+    let mut offset = 0;
+    for _ in cycle_profile_left_event_reader.iter() {
+        offset -= 1;
+    }
+    for _ in cycle_profile_right_event_reader.iter() {
+        offset += 1;
+    }
+    if offset == 0 {
+        return;
+    }
+    movement_profiles.selected = cycle_index(
+        movement_profiles.selected,
+        movement_profiles.profiles.len(),
+        offset,
+    );
+    game_state.movement_profile = movement_profiles.selected().clone();
+    settings.movement_profile = game_state.movement_profile.clone();
+    game_state.path = Vec::new();
+    println!("Movement profile: {}", game_state.movement_profile.name);
+    reset_event_writer.send(ResetEvent {});
+}
+
+// The label takes the colour the profile's path is drawn in.
+pub fn update_current_profile_text_system(
+    mut current_profile_text_query: Query<&mut Text, With<CurrentProfileText>>,
+    movement_profiles: Res<MovementProfiles>,
+) {
+    if !movement_profiles.is_changed() {
+        return;
+    }
+    let label = format!("Profile: {}", movement_profiles.selected().name);
+    let color = PROFILE_PATH_COLORS[movement_profiles.selected % PROFILE_PATH_COLORS.len()];
+    for mut text in &mut current_profile_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value.clone_from(&label);
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+pub fn update_solve_button_text_system(
+    mut solve_button_text_query: Query<&mut Text, With<SolveButtonText>>,
+    solver: Res<Solver>,
+) {
+    if !solver.is_changed() {
+        return;
+    }
+    let label = if solver.pending.is_some() {
+        "Solving..."
+    } else {
+        "Solve"
+    };
+    for mut text in &mut solve_button_text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.to_string();
+        }
+    }
+}
+
+// This is a hack to solve my issue to ray casts going through buttons.
+// Every time a button is clicked or hovered over, I send a `UserInterfaceInteractionEvent`
+// Which other systems can read and then return from immediately.
+pub fn send_ui_interaction_events_system(
+    mut user_interface_interaction_event_writer: EventWriter<UserInterfaceInteractionEvent>,
+    button_query: Query<&Interaction, With<Button>>,
+) {
+    for interaction in &button_query {
+        if matches!(*interaction, Interaction::Clicked | Interaction::Hovered) {
+            user_interface_interaction_event_writer.send(UserInterfaceInteractionEvent {});
+        }
+    }
+}